@@ -0,0 +1,53 @@
+//! Parses every element of arbitrary XML with every `Parsable` impl, so that each is fuzzed without having to get
+//! through the response around it first.
+//!
+//! Each element is parsed both with the string it came from and with a different one, as the parsers are public and
+//! nothing stops a caller mixing them up.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use openldbsvws_lib::{
+    Association, Coach, DepartureItem, DisruptionMessage, Formation, Location, Parsable, Reason,
+    ReasonCode, ServiceItem, ServiceLocation, ServiceSummary, ServiceTime, SourceInstance, Station,
+    TimelineEntry, Toc,
+};
+use roxmltree::{Document, Node};
+
+fn parse_all<'a>(node: &Node<'a, 'a>, string: &str) {
+    let _ = Association::parse(node, string);
+    let _ = Coach::parse(node, string);
+    let _ = DepartureItem::parse(node, string);
+    let _ = DisruptionMessage::parse(node, string);
+    let _ = Formation::parse(node, string);
+    let _ = Location::parse(node, string);
+    let _ = Reason::parse(node, string);
+    let _ = ReasonCode::parse(node, string);
+    let _ = ServiceItem::parse(node, string);
+    let _ = ServiceLocation::parse(node, string);
+    let _ = ServiceSummary::parse(node, string);
+    let _ = ServiceTime::parse(node, string);
+    let _ = SourceInstance::parse(node, string);
+    let _ = Station::parse(node, string);
+    let _ = TimelineEntry::parse(node, string);
+    let _ = Toc::parse(node, string);
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(string) = std::str::from_utf8(data) {
+        if let Ok(document) = Document::parse(string) {
+            // Half of the input, cut at a character boundary.
+            let mut half = string.len() / 2;
+
+            while !string.is_char_boundary(half) {
+                half -= 1;
+            }
+
+            for node in document.descendants().filter(|x| x.is_element()) {
+                parse_all(&node, string);
+                parse_all(&node, &string[..half]);
+                parse_all(&node, "");
+            }
+        }
+    }
+});
@@ -0,0 +1,24 @@
+//! Parses arbitrary input as every other kind of response.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use openldbsvws_lib::{
+    DeparturesBoard, DisruptionList, ReasonCode, ReasonCodeList, ServiceList, SourceInstanceList,
+    StationBoard, StationList, Timeline, TocList,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(string) = std::str::from_utf8(data) {
+        let _ = StationBoard::try_from(string);
+        let _ = DeparturesBoard::try_from(string);
+        let _ = DisruptionList::try_from(string);
+        let _ = Timeline::try_from(string);
+        let _ = ServiceList::try_from(string);
+        let _ = ReasonCode::try_from(string);
+        let _ = ReasonCodeList::try_from(string);
+        let _ = TocList::try_from(string);
+        let _ = StationList::try_from(string);
+        let _ = SourceInstanceList::try_from(string);
+    }
+});
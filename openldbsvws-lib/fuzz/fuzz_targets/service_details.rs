@@ -0,0 +1,17 @@
+//! Parses arbitrary input as service details, strictly and leniently. Either may fail, but neither may panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use openldbsvws_lib::ServiceDetails;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(string) = std::str::from_utf8(data) {
+        if let Ok(details) = ServiceDetails::try_from(string) {
+            // Owning the details copies every borrowed field, so touches everything that was parsed.
+            let _ = details.into_owned();
+        }
+
+        let _ = ServiceDetails::parse_lenient(string);
+    }
+});
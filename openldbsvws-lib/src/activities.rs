@@ -0,0 +1,394 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Activity codes.
+///
+/// See [Activity Codes](https://wiki.openraildata.com//index.php?title=Activity_codes) on the
+/// Open Rail Data Wiki.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Activity {
+    /// Stops to detach vehicles. (-D)
+    StopDetach,
+    /// Stops to attach and detach vehicles. (-T)
+    StopAttachDetach,
+    /// Stops to attach vehicles. (-U)
+    StopAttach,
+    /// Stops or shunts for other trains to pass. (A)
+    StopOrShuntForPass,
+    /// Attaches or detaches an assisting locomotive. (AE)
+    AttachOrDetachAssistingLocomotive,
+    /// Shows as 'X' on arrival. (AX)
+    ///
+    /// Nor the Open Rail Data Wiki nor the CIF User Spec provide useful information on this.
+    ShowsAsXOnArrival,
+    /// Stops for banking locomotive. (BL)
+    StopsForBankingLocomotive,
+    /// Stops to change train crew. (C)
+    StopsToChangeCrew,
+    /// Stops to set down passengers. (D)
+    ///
+    /// Passengers may not board here.
+    StopsToSetDownPassengers,
+    /// Stops for examination. (E)
+    StopsForExamination,
+    /// GBPRTT (Great British Railways Transition Team) Data to add. (G)
+    GBPRTTDataToAdd,
+    /// Notional activity to prevent WTT columns merge. (H)
+    ///
+    /// This can probably be safely treated as no activity.
+    Notional,
+    /// Notional activity to prevent WTT columns merge where 3rd column. (HH)
+    ///
+    /// This can probably be safely treated as no activity.
+    NotionalActivityThirdColumn,
+    /// Passenger count point. (K)
+    PassengerCountPoint,
+    /// Ticket collection and examination point. (KC)
+    TicketCollectionAndExaminationPoint,
+    /// Ticket examination point. (KE)
+    TicketExaminationPoint,
+    /// Ticket examination point for first class only. (KF)
+    TicketExaminationPointFirstClass,
+    /// Selective ticket examination point. (KS)
+    SelectiveTicketExaminationPoint,
+    /// Stops to change locomotive. (L)
+    StopsToChangeLocomotive,
+    /// Stop not advertised. (N)
+    StopNotAdvertised,
+    /// Stops for other operating reasons. (OP)
+    StopsForOtherReasons,
+    /// Train locomotive on rear. (OR)
+    TrainLocomotiveOnRear,
+    /// Propelling between points shown. (PR)
+    PropellingBetweenPointsShown,
+    /// Stops when required. (R)
+    StopsWhenRequired,
+    /// Stops for reversing move or when the driver changes ends. (RM)
+    StopsForReversingMove,
+    /// Stops for locomotive to run round train. (RR)
+    StopsForLocomotiveToRunRoundTrain,
+    /// Stops for railway personnel only. (S)
+    StopsForRailwayPersonnel,
+    /// Stops to take up and set down passengers. (T)
+    ///
+    /// Passengers may board and exit the train.
+    StopsToTakeUpAndSetDownPassengers,
+    /// Train begins. (TB)
+    TrainBegins,
+    /// Train finishes. (TF)
+    TrainFinishes,
+    /// Activity requested for TOPS reporting purposes. (TS)
+    RequestedForTOPS,
+    /// Stops or passes for tablet, staff or token. (TW)
+    StopsOrPassesForTabletOrStaffOrToken,
+    /// Stops to take up passengers. (U)
+    ///
+    /// Passengers may not exit the train.
+    StopsToTakeUpPassengers,
+    /// Stops for watering of coaches. (W)
+    StopsForWateringOfCoaches,
+    /// Passes another train at crossing point on a single line. (X)
+    PassesAnotherTrain,
+    /// No activity.
+    None,
+}
+
+impl Activity {
+    /// Every activity, in the order of their codes. `None` is last.
+    pub const ALL: [Activity; 36] = [
+        Activity::StopDetach,
+        Activity::StopAttachDetach,
+        Activity::StopAttach,
+        Activity::StopOrShuntForPass,
+        Activity::AttachOrDetachAssistingLocomotive,
+        Activity::ShowsAsXOnArrival,
+        Activity::StopsForBankingLocomotive,
+        Activity::StopsToChangeCrew,
+        Activity::StopsToSetDownPassengers,
+        Activity::StopsForExamination,
+        Activity::GBPRTTDataToAdd,
+        Activity::Notional,
+        Activity::NotionalActivityThirdColumn,
+        Activity::PassengerCountPoint,
+        Activity::TicketCollectionAndExaminationPoint,
+        Activity::TicketExaminationPoint,
+        Activity::TicketExaminationPointFirstClass,
+        Activity::SelectiveTicketExaminationPoint,
+        Activity::StopsToChangeLocomotive,
+        Activity::StopNotAdvertised,
+        Activity::StopsForOtherReasons,
+        Activity::TrainLocomotiveOnRear,
+        Activity::PropellingBetweenPointsShown,
+        Activity::StopsWhenRequired,
+        Activity::StopsForReversingMove,
+        Activity::StopsForLocomotiveToRunRoundTrain,
+        Activity::StopsForRailwayPersonnel,
+        Activity::StopsToTakeUpAndSetDownPassengers,
+        Activity::TrainBegins,
+        Activity::TrainFinishes,
+        Activity::RequestedForTOPS,
+        Activity::StopsOrPassesForTabletOrStaffOrToken,
+        Activity::StopsToTakeUpPassengers,
+        Activity::StopsForWateringOfCoaches,
+        Activity::PassesAnotherTrain,
+        Activity::None,
+    ];
+
+    /// The CIF code of this activity, such as "T" or "-D", without padding. `None` has an empty code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Activity::StopDetach => "-D",
+            Activity::StopAttachDetach => "-T",
+            Activity::StopAttach => "-U",
+            Activity::StopOrShuntForPass => "A",
+            Activity::AttachOrDetachAssistingLocomotive => "AE",
+            Activity::ShowsAsXOnArrival => "AX",
+            Activity::StopsForBankingLocomotive => "BL",
+            Activity::StopsToChangeCrew => "C",
+            Activity::StopsToSetDownPassengers => "D",
+            Activity::StopsForExamination => "E",
+            Activity::GBPRTTDataToAdd => "G",
+            Activity::Notional => "H",
+            Activity::NotionalActivityThirdColumn => "HH",
+            Activity::PassengerCountPoint => "K",
+            Activity::TicketCollectionAndExaminationPoint => "KC",
+            Activity::TicketExaminationPoint => "KE",
+            Activity::TicketExaminationPointFirstClass => "KF",
+            Activity::SelectiveTicketExaminationPoint => "KS",
+            Activity::StopsToChangeLocomotive => "L",
+            Activity::StopNotAdvertised => "N",
+            Activity::StopsForOtherReasons => "OP",
+            Activity::TrainLocomotiveOnRear => "OR",
+            Activity::PropellingBetweenPointsShown => "PR",
+            Activity::StopsWhenRequired => "R",
+            Activity::StopsForReversingMove => "RM",
+            Activity::StopsForLocomotiveToRunRoundTrain => "RR",
+            Activity::StopsForRailwayPersonnel => "S",
+            Activity::StopsToTakeUpAndSetDownPassengers => "T",
+            Activity::TrainBegins => "TB",
+            Activity::TrainFinishes => "TF",
+            Activity::RequestedForTOPS => "TS",
+            Activity::StopsOrPassesForTabletOrStaffOrToken => "TW",
+            Activity::StopsToTakeUpPassengers => "U",
+            Activity::StopsForWateringOfCoaches => "W",
+            Activity::PassesAnotherTrain => "X",
+            Activity::None => "",
+        }
+    }
+
+    /// A short description of this activity, such as "Stops to take up and set down passengers".
+    pub fn description(&self) -> &'static str {
+        match self {
+            Activity::StopDetach => "Stops to detach vehicles",
+            Activity::StopAttachDetach => "Stops to attach and detach vehicles",
+            Activity::StopAttach => "Stops to attach vehicles",
+            Activity::StopOrShuntForPass => "Stops or shunts for other trains to pass",
+            Activity::AttachOrDetachAssistingLocomotive => {
+                "Attaches or detaches an assisting locomotive"
+            }
+            Activity::ShowsAsXOnArrival => "Shows as 'X' on arrival",
+            Activity::StopsForBankingLocomotive => "Stops for banking locomotive",
+            Activity::StopsToChangeCrew => "Stops to change train crew",
+            Activity::StopsToSetDownPassengers => "Stops to set down passengers",
+            Activity::StopsForExamination => "Stops for examination",
+            Activity::GBPRTTDataToAdd => "GBPRTT data to add",
+            Activity::Notional => "Notional activity to prevent WTT columns merge",
+            Activity::NotionalActivityThirdColumn => {
+                "Notional activity to prevent WTT columns merge, where 3rd column"
+            }
+            Activity::PassengerCountPoint => "Passenger count point",
+            Activity::TicketCollectionAndExaminationPoint => {
+                "Ticket collection and examination point"
+            }
+            Activity::TicketExaminationPoint => "Ticket examination point",
+            Activity::TicketExaminationPointFirstClass => {
+                "Ticket examination point, first class only"
+            }
+            Activity::SelectiveTicketExaminationPoint => "Selective ticket examination point",
+            Activity::StopsToChangeLocomotive => "Stops to change locomotive",
+            Activity::StopNotAdvertised => "Stop not advertised",
+            Activity::StopsForOtherReasons => "Stops for other operating reasons",
+            Activity::TrainLocomotiveOnRear => "Train locomotive on rear",
+            Activity::PropellingBetweenPointsShown => "Propelling between points shown",
+            Activity::StopsWhenRequired => "Stops when required",
+            Activity::StopsForReversingMove => {
+                "Stops for reversing move or when the driver changes ends"
+            }
+            Activity::StopsForLocomotiveToRunRoundTrain => {
+                "Stops for locomotive to run round train"
+            }
+            Activity::StopsForRailwayPersonnel => "Stops for railway personnel only",
+            Activity::StopsToTakeUpAndSetDownPassengers => {
+                "Stops to take up and set down passengers"
+            }
+            Activity::TrainBegins => "Train begins",
+            Activity::TrainFinishes => "Train finishes",
+            Activity::RequestedForTOPS => "Activity requested for TOPS reporting purposes",
+            Activity::StopsOrPassesForTabletOrStaffOrToken => {
+                "Stops or passes for tablet, staff or token"
+            }
+            Activity::StopsToTakeUpPassengers => "Stops to take up passengers",
+            Activity::StopsForWateringOfCoaches => "Stops for watering of coaches",
+            Activity::PassesAnotherTrain => {
+                "Passes another train at crossing point on a single line"
+            }
+            Activity::None => "No activity",
+        }
+    }
+
+    /// If true, passengers may board the train when it stops for this activity.
+    pub fn picks_up(&self) -> bool {
+        matches!(
+            self,
+            Activity::StopsToTakeUpAndSetDownPassengers | Activity::StopsToTakeUpPassengers
+        )
+    }
+
+    /// If true, passengers may leave the train when it stops for this activity.
+    pub fn sets_down(&self) -> bool {
+        matches!(
+            self,
+            Activity::StopsToTakeUpAndSetDownPassengers | Activity::StopsToSetDownPassengers
+        )
+    }
+}
+
+impl Display for Activity {
+    /// Writes the CIF code of this activity, padded to two characters as in the CIF, such as "T " or "-D". Wider
+    /// widths pad further.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:<2}", self.code()))
+    }
+}
+
+/// An unknown activity code was given.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown activity code {0:?}")]
+pub struct UnknownActivity(pub String);
+
+impl FromStr for Activity {
+    type Err = UnknownActivity;
+
+    /// Parses a single activity code, such as "T", "T " or "-D". Single character codes may be padded with spaces, and
+    /// a blank code is `None`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+
+        Activity::ALL
+            .into_iter()
+            .find(|activity| activity.code() == code)
+            .ok_or_else(|| UnknownActivity(s.to_string()))
+    }
+}
+
+/// The activities at a location, as in the 12 character activity field of the CIF. The field holds up to six codes of
+/// two characters each, with single character codes padded with a space, such as "T -D".
+///
+/// Blank codes are left out, so a field of only spaces has no activities.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Activities(Vec<Activity>);
+
+impl Activities {
+    /// Splits an activity field into its codes, without parsing them. Darwin trims trailing spaces, so the last code
+    /// may be a single character. If the field isn't ASCII, a code which would split a character is the whole field.
+    pub(crate) fn codes(field: &str) -> impl Iterator<Item = &str> {
+        (0..field.len()).step_by(2).map(move |start| {
+            field
+                .get(start..field.len().min(start + 2))
+                .unwrap_or(field)
+        })
+    }
+
+    /// Whether the train calls here for passengers. Stops not advertised to the public aren't public stops, even if
+    /// they have other activities.
+    pub fn is_public_stop(&self) -> bool {
+        !self.contains(&Activity::StopNotAdvertised)
+            && (self.picks_up() || self.sets_down() || self.is_request_stop())
+    }
+
+    /// If true, passengers may board the train here.
+    pub fn picks_up(&self) -> bool {
+        self.iter().any(Activity::picks_up)
+    }
+
+    /// If true, passengers may leave the train here.
+    pub fn sets_down(&self) -> bool {
+        self.iter().any(Activity::sets_down)
+    }
+
+    /// If true, the train only stops here when required. Passengers wanting to leave must tell the guard, and those
+    /// wanting to board must signal to the driver.
+    pub fn is_request_stop(&self) -> bool {
+        self.contains(&Activity::StopsWhenRequired)
+    }
+}
+
+impl Deref for Activities {
+    type Target = [Activity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<Activity>> for Activities {
+    /// Collects activities, leaving out any `None`.
+    fn from(activities: Vec<Activity>) -> Self {
+        activities.into_iter().collect()
+    }
+}
+
+impl FromIterator<Activity> for Activities {
+    fn from_iter<T: IntoIterator<Item = Activity>>(iter: T) -> Self {
+        Activities(
+            iter.into_iter()
+                .filter(|activity| *activity != Activity::None)
+                .collect(),
+        )
+    }
+}
+
+impl IntoIterator for Activities {
+    type Item = Activity;
+    type IntoIter = std::vec::IntoIter<Activity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Activities {
+    type Item = &'a Activity;
+    type IntoIter = std::slice::Iter<'a, Activity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Display for Activities {
+    /// Writes the activities as a CIF activity field, with each code padded to two characters. The field is only as
+    /// long as it needs to be, so use a width of 12 for the fixed-width field, such as `format!("{:12}", activities)`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let field: String = self.iter().map(|activity| activity.to_string()).collect();
+
+        f.pad(&field)
+    }
+}
+
+impl FromStr for Activities {
+    type Err = UnknownActivity;
+
+    /// Parses an activity field, such as "TB", "T -D" or "T           ". Every code must be known.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Activities::codes(s).map(Activity::from_str).collect()
+    }
+}
@@ -3,9 +3,10 @@ use roxmltree::Node;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::parsable::{Parsable, ParsingError};
+use crate::identifiers::{Headcode, Rid, Rsid, Uid};
+use crate::parsable::{Mode, Parsable, ParsingError};
 use crate::services::Location;
-use crate::{bool, date, name, text};
+use crate::{bool, date, decoded_text, identifier, name, optional_identifier, text};
 
 /// Train association categories.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -28,13 +29,13 @@ pub struct Association<'a> {
     /// The association category.
     pub category: AssociationCategory,
     /// A unique RTTI ID for this service that can be used to obtain full details of the service.
-    pub rid: &'a str,
+    pub rid: Rid<'a>,
     /// The TSDB Train UID value for this service, or if one is not available, then an RTTI allocated replacement.
-    pub uid: &'a str,
+    pub uid: Uid<'a>,
     /// The Train ID value (headcode) for this service.
-    pub trainid: &'a str,
+    pub trainid: Headcode<'a>,
     /// The Retail Service ID for this service, if known.
-    pub rsid: Option<&'a str>,
+    pub rsid: Option<Rsid<'a>>,
     /// The Scheduled Departure Date of this service.
     pub sdd: NaiveDate,
     /// The origin location of the associated service.
@@ -45,8 +46,31 @@ pub struct Association<'a> {
     pub cancelled: bool,
 }
 
-impl<'a, 'b> Parsable<'a, 'a, 'b> for Association<'b> {
-    fn parse(association: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+impl<'a> Association<'a> {
+    /// Copies any borrowed strings, so that this association no longer borrows from the response.
+    pub fn into_owned(self) -> Association<'static> {
+        Association {
+            category: self.category,
+            rid: self.rid.into_owned(),
+            uid: self.uid.into_owned(),
+            trainid: self.trainid.into_owned(),
+            rsid: self.rsid.map(Rsid::into_owned),
+            sdd: self.sdd,
+            origin: self.origin.map(Location::into_owned),
+            destination: self.destination.map(Location::into_owned),
+            cancelled: self.cancelled,
+        }
+    }
+}
+
+impl<'b> Association<'b> {
+    /// Parses an association. In lenient mode, invalid identifiers are kept or left out with a warning, as with
+    /// `ServiceDetails::parse_lenient`.
+    pub(crate) fn parse_with<'a>(
+        association: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<Self, ParsingError<'b>> {
         if name!(association) != "association" {
             return Err(ParsingError::InvalidTagName("association"));
         }
@@ -56,24 +80,30 @@ impl<'a, 'b> Parsable<'a, 'a, 'b> for Association<'b> {
                 "divide" => AssociationCategory::Divide,
                 "join" => AssociationCategory::Join,
                 "next" => AssociationCategory::Next,
-                x => return Err(ParsingError::InvalidAssociationCategory(x)),
+                x => return Err(ParsingError::InvalidAssociationCategory(x.into())),
             },
-            rid: text!(string, association, "rid")?,
-            uid: text!(string, association, "uid")?,
-            trainid: text!(string, association, "trainid")?,
-            rsid: text!(string, association, "rsid").ok(),
+            rid: identifier!(mode, string, association, "rid")?,
+            uid: identifier!(mode, string, association, "uid")?,
+            trainid: identifier!(mode, string, association, "trainid")?,
+            rsid: optional_identifier!(mode, string, association, "rsid")?,
             sdd: date!(string, association, "sdd")?,
             origin: Some(Location {
-                name: text!(string, association, "origin")?,
-                crs: text!(string, association, "originCRS").ok(),
-                tiploc: text!(string, association, "originTiploc").ok(),
+                name: decoded_text!(string, association, "origin")?,
+                crs: optional_identifier!(mode, string, association, "originCRS")?,
+                tiploc: optional_identifier!(mode, string, association, "originTiploc")?,
             }),
             destination: Some(Location {
-                name: text!(string, association, "destination")?,
-                crs: text!(string, association, "destCRS").ok(),
-                tiploc: text!(string, association, "destTiploc").ok(),
+                name: decoded_text!(string, association, "destination")?,
+                crs: optional_identifier!(mode, string, association, "destCRS")?,
+                tiploc: optional_identifier!(mode, string, association, "destTiploc")?,
             }),
             cancelled: bool!(string, association, "cancelled", false)?,
         })
     }
 }
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Association<'b> {
+    fn parse(association: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        Association::parse_with(association, string, &mut Mode::Strict)
+    }
+}
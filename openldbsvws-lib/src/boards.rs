@@ -0,0 +1,554 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::category::TrainCategory;
+use crate::identifiers::{Headcode, Rid, Rsid, Uid};
+use crate::parsable::{missing_result, owned, Mode, Parsable, ParsingError, ParsingWarning};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{
+    format_scheduled, PrettyOptions, PrettyPrintable, CANCELLED, GREY, INDENT, LATE,
+    LIGHT_SCHEDULED, PASSED, PURPLE, SCHEDULED,
+};
+use crate::reasons::{Reason, ReasonCatalogue};
+#[cfg(feature = "pretty")]
+use crate::services::{Lateness, UserLateness};
+use crate::services::{Location, ServiceTime};
+use crate::{
+    bool, child, date, decoded_text, identifier, name, optional_identifier, parse, text, time,
+};
+
+/// Filter types for station boards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    /// Only services which travel to the filter location after this location.
+    To,
+    /// Only services which travelled from the filter location before this location.
+    From,
+}
+
+impl FilterType {
+    /// The value of this filter type in a request or response.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterType::To => "to",
+            FilterType::From => "from",
+        }
+    }
+}
+
+/// The maximum number of services a board can be requested with.
+pub const MAX_ROWS: u16 = 150;
+
+/// The maximum time window of a board or departures request, in minutes.
+pub const MAX_TIME_WINDOW: u16 = 1440;
+
+/// Options for station board requests. Every option is optional; the defaults match the defaults of OpenLDBSVWS.
+#[derive(Debug, Clone, Default)]
+pub struct BoardOptions<'a> {
+    /// The maximum number of services to return, between 1 and `MAX_ROWS`. Defaults to 150.
+    pub num_rows: Option<u16>,
+    /// The time the board is generated for. Defaults to now.
+    pub time: Option<DateTime<FixedOffset>>,
+    /// An offset in minutes from `time`, which may be negative to see services which have already left.
+    pub time_offset: Option<i32>,
+    /// The number of minutes after `time` (plus `time_offset`) to return services for, up to `MAX_TIME_WINDOW`.
+    /// Defaults to 120.
+    pub time_window: Option<u16>,
+    /// Only return services which call at this location. See `filter_type`. Only used by boards requested by CRS.
+    pub filter_crs: Option<&'a str>,
+    /// Only return services which call at or pass this location. See `filter_type`. Only used by boards requested by
+    /// TIPLOC.
+    pub filter_tiploc: Option<&'a str>,
+    /// Whether the filter location filters services travelling to or from it. Defaults to `FilterType::To`.
+    pub filter_type: Option<FilterType>,
+    /// If true, non-passenger services such as freight and empty coaching stock are included.
+    pub non_passenger_services: bool,
+}
+
+/// A service on a station board.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ServiceItem<'a> {
+    /// A unique RTTI ID for this service that can be used to obtain full details of the service.
+    pub rid: Rid<'a>,
+    /// The TSDB Train UID value for this service, or if one is not available, then an RTTI allocated replacement.
+    pub uid: Uid<'a>,
+    /// The Train ID value (headcode) for this service.
+    pub trainid: Headcode<'a>,
+    /// The Retail Service ID of the service, if known.
+    pub rsid: Option<Rsid<'a>>,
+    /// The Scheduled Departure Date of this service.
+    pub sdd: NaiveDate,
+    /// The operator of this service.
+    pub operator: Cow<'a, str>,
+    /// The operator code of this service.
+    pub operator_code: Cow<'a, str>,
+    /// If true, this is a passenger service. Non-passenger services should not be published to the public.
+    pub passenger_service: bool,
+    /// If true, this is a charter service.
+    pub charter: bool,
+    /// The category of this service. Non-passenger services have categories such as `EmptyCoachingStock` (EE) or
+    /// `LightLocomotive` (ZZ).
+    pub category: TrainCategory,
+    /// If true, this is an operational calling location. Times will be working times, rather than the usual public
+    /// times.
+    pub operational: bool,
+    /// If true, the service passes (does not stop) at this location. No arrival times will be specified and the
+    /// departure times should be interpreted as working pass times. See `ServiceLocation::pass`.
+    pub pass: bool,
+    /// The origins of this service. A service may have more than one origin if it is formed by joining services.
+    pub origin: Vec<Location<'a>>,
+    /// The destinations of this service. A service may have more than one destination if it divides.
+    pub destination: Vec<Location<'a>>,
+    /// The current origins of this service, if the service no longer starts from its scheduled origin.
+    pub current_origins: Option<Vec<Location<'a>>>,
+    /// The current destinations of this service, if the service no longer runs to its scheduled destination.
+    pub current_destinations: Option<Vec<Location<'a>>>,
+    /// The arrival and departure time of this service at the board's location.
+    pub time: ServiceTime<'a>,
+    /// The platform number that the service is expected to use. If None, the platform is not known.
+    pub platform: Option<u8>,
+    /// If true, the platform number should not be displayed to the public.
+    pub platform_hidden: bool,
+    /// If true, the service has been suppressed and will not be displayed at the station.
+    pub suppressed: bool,
+    /// If true, the service is cancelled at this location.
+    pub cancelled: bool,
+    /// The cancellation reason, which is not always provided.
+    pub cancel_reason: Option<Reason<'a>>,
+    /// The delay reason, which is not always provided.
+    pub delay_reason: Option<Reason<'a>>,
+    /// The length of the train. If None, the length is unknown.
+    pub length: Option<u16>,
+    /// Whether the front is detached at this location.
+    pub detach_front: bool,
+    /// If true, this service is operating in the reverse of its normal formation.
+    pub reverse_formation: bool,
+}
+
+/// Copies a list of locations, so that it no longer borrows from the response.
+fn owned_locations(locations: Vec<Location>) -> Vec<Location<'static>> {
+    locations.into_iter().map(Location::into_owned).collect()
+}
+
+/// Parses a list of `location` elements, such as `origin` or `destination`.
+fn locations<'a, 'b>(
+    list: &Node<'a, 'a>,
+    string: &'b str,
+    mode: &mut Mode,
+) -> Result<Vec<Location<'b>>, ParsingError<'b>> {
+    let mut vec = Vec::new();
+
+    for node in list.children().filter(|x| x.has_tag_name("location")) {
+        vec.push(Location::parse_with(&node, string, mode).map_err(|e| e.at(&node))?)
+    }
+
+    Ok(vec)
+}
+
+impl<'a> ServiceItem<'a> {
+    /// Copies any borrowed strings, so that this service no longer borrows from the response.
+    pub fn into_owned(self) -> ServiceItem<'static> {
+        ServiceItem {
+            rid: self.rid.into_owned(),
+            uid: self.uid.into_owned(),
+            trainid: self.trainid.into_owned(),
+            rsid: self.rsid.map(Rsid::into_owned),
+            sdd: self.sdd,
+            operator: owned(self.operator),
+            operator_code: owned(self.operator_code),
+            passenger_service: self.passenger_service,
+            charter: self.charter,
+            category: self.category,
+            operational: self.operational,
+            pass: self.pass,
+            origin: owned_locations(self.origin),
+            destination: owned_locations(self.destination),
+            current_origins: self.current_origins.map(owned_locations),
+            current_destinations: self.current_destinations.map(owned_locations),
+            time: self.time.into_owned(),
+            platform: self.platform,
+            platform_hidden: self.platform_hidden,
+            suppressed: self.suppressed,
+            cancelled: self.cancelled,
+            cancel_reason: self.cancel_reason.map(Reason::into_owned),
+            delay_reason: self.delay_reason.map(Reason::into_owned),
+            length: self.length,
+            detach_front: self.detach_front,
+            reverse_formation: self.reverse_formation,
+        }
+    }
+
+    /// The scheduled time of this service at the board's location, depending on whether it passes or is operational.
+    /// See `ServiceTime::effective_scheduled`.
+    pub fn effective_scheduled(&self) -> Option<DateTime<FixedOffset>> {
+        self.time.effective_scheduled(self.pass, self.operational)
+    }
+
+    /// Resolves the cancellation and delay reasons of this service into text, naming their location if it is the
+    /// origin or destination.
+    pub fn resolve_reasons(&mut self, catalogue: &ReasonCatalogue) {
+        let locations: Vec<&Location> = self.origin.iter().chain(&self.destination).collect();
+        let name = |reason: &Reason| {
+            locations
+                .iter()
+                .find(|x| x.tiploc.is_some() && x.tiploc.as_deref() == reason.tiploc.as_deref())
+                .map(|x| &*x.name)
+        };
+
+        if let Some(reason) = &mut self.cancel_reason {
+            reason.text = catalogue.resolve_cancellation(reason, name(reason));
+        }
+
+        if let Some(reason) = &mut self.delay_reason {
+            reason.text = catalogue.resolve_late(reason, name(reason));
+        }
+    }
+}
+
+impl<'b> ServiceItem<'b> {
+    /// Parses a service. In lenient mode, invalid identifiers are kept or left out with a warning, as with
+    /// `ServiceDetails::parse_lenient`.
+    pub(crate) fn parse_with<'a>(
+        service: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<ServiceItem<'b>, ParsingError<'b>> {
+        if name!(service) != "service" {
+            return Err(ParsingError::InvalidTagName("service"));
+        }
+
+        Ok(ServiceItem {
+            rid: identifier!(mode, string, service, "rid")?,
+            uid: identifier!(mode, string, service, "uid")?,
+            trainid: identifier!(mode, string, service, "trainid")?,
+            rsid: optional_identifier!(mode, string, service, "rsid")?,
+            sdd: date!(string, service, "sdd")?,
+            operator: decoded_text!(string, service, "operator")?,
+            operator_code: text!(string, service, "operatorCode")?.into(),
+            passenger_service: bool!(string, service, "isPassengerService", true)?,
+            charter: bool!(string, service, "isCharter", false)?,
+            category: text!(string, service, "category")?.into(),
+            operational: bool!(string, service, "isOperational", false)?,
+            pass: bool!(string, service, "isPass", false)?,
+            origin: locations(&child!(service, "origin")?, string, mode)?,
+            destination: locations(&child!(service, "destination")?, string, mode)?,
+            current_origins: match child!(service, "currentOrigins").ok() {
+                Some(list) => Some(locations(&list, string, mode)?),
+                None => None,
+            },
+            current_destinations: match child!(service, "currentDestinations").ok() {
+                Some(list) => Some(locations(&list, string, mode)?),
+                None => None,
+            },
+            time: ServiceTime::parse_located(service, string)?,
+            platform: parse!(string, service, "platform", u8).ok(),
+            platform_hidden: bool!(string, service, "platformIsHidden", false)?,
+            // The docs make this misspelling. Is it a mistake? Who knows!
+            suppressed: bool!(string, service, "serviceIsSupressed", false)?,
+            cancelled: bool!(string, service, "isCancelled", false)?,
+            cancel_reason: match child!(service, "cancelReason") {
+                Ok(reason) => Some(Reason::parse_located(&reason, string)?),
+                Err(_) => None,
+            },
+            delay_reason: match child!(service, "delayReason") {
+                Ok(reason) => Some(Reason::parse_located(&reason, string)?),
+                Err(_) => None,
+            },
+            length: match parse!(string, service, "length", u16) {
+                Ok(0) | Err(_) => None,
+                Ok(x) => Some(x),
+            },
+            detach_front: bool!(string, service, "detachFront", false)?,
+            reverse_formation: bool!(string, service, "isReverseFormation", false)?,
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceItem<'b> {
+    fn parse(service: &Node<'a, 'a>, string: &'b str) -> Result<ServiceItem<'b>, ParsingError<'b>> {
+        ServiceItem::parse_with(service, string, &mut Mode::Strict)
+    }
+}
+
+/// A station board, listing the arrivals and/or departures at a location.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct StationBoard<'a> {
+    /// The time this board was generated.
+    pub generated_at: DateTime<FixedOffset>,
+    /// The location of this board.
+    pub location: Location<'a>,
+    /// The location services were filtered by, if any.
+    pub filter_location: Option<Location<'a>>,
+    /// Whether services were filtered to or from `filter_location`.
+    pub filter_type: Option<FilterType>,
+    /// If true, platforms should not be displayed to the public at this location.
+    pub platforms_hidden: bool,
+    /// If true, services at this location are not available, so the board is empty.
+    pub services_unavailable: bool,
+    /// If true, there were more services than requested, and the board has been truncated.
+    pub truncated: bool,
+    /// The train services on this board.
+    pub train_services: Vec<ServiceItem<'a>>,
+    /// The bus services on this board.
+    pub bus_services: Vec<ServiceItem<'a>>,
+    /// The ferry services on this board.
+    pub ferry_services: Vec<ServiceItem<'a>>,
+    /// Problems found while parsing leniently with `parse_lenient`. Always empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<ParsingWarning>,
+}
+
+/// Parses a list of `service` elements, such as `trainServices`. A missing list is empty. In lenient mode, a service
+/// which can't be parsed is left out with a warning.
+fn services<'a, 'b>(
+    list: Option<Node<'a, 'a>>,
+    string: &'b str,
+    mode: &mut Mode,
+) -> Result<Vec<ServiceItem<'b>>, ParsingError<'b>> {
+    let mut vec = Vec::new();
+
+    if let Some(list) = list {
+        for node in list.children().filter(|x| x.is_element()) {
+            let service = ServiceItem::parse_with(&node, string, mode);
+
+            if let Some(service) = mode.recover(&node, service)? {
+                vec.push(service)
+            }
+        }
+    }
+
+    Ok(vec)
+}
+
+/// Copies a list of services, so that it no longer borrows from the response.
+fn owned_services(services: Vec<ServiceItem>) -> Vec<ServiceItem<'static>> {
+    services.into_iter().map(ServiceItem::into_owned).collect()
+}
+
+impl<'a> StationBoard<'a> {
+    /// Copies any borrowed strings, so that this board no longer borrows from the response.
+    pub fn into_owned(self) -> StationBoard<'static> {
+        StationBoard {
+            generated_at: self.generated_at,
+            location: self.location.into_owned(),
+            filter_location: self.filter_location.map(Location::into_owned),
+            filter_type: self.filter_type,
+            platforms_hidden: self.platforms_hidden,
+            services_unavailable: self.services_unavailable,
+            truncated: self.truncated,
+            train_services: owned_services(self.train_services),
+            bus_services: owned_services(self.bus_services),
+            ferry_services: owned_services(self.ferry_services),
+            warnings: self.warnings,
+        }
+    }
+
+    /// Parses a station board leniently. Unlike `try_from`, a service which can't be parsed is left out with a warning
+    /// in `warnings`, and invalid identifiers are kept as they are, or left out if optional, with a warning.
+    pub fn parse_lenient(string: &'a str) -> Result<StationBoard<'a>, ParsingError<'a>> {
+        let mut warnings = Vec::new();
+        let mut board = StationBoard::parse_with(string, &mut Mode::Lenient(&mut warnings))?;
+
+        board.warnings = warnings;
+
+        Ok(board)
+    }
+
+    /// Finds and parses the result of a response.
+    fn parse_with(string: &'a str, mode: &mut Mode) -> Result<StationBoard<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let board = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetBoardResult") || x.has_tag_name("GetHistoricBoardResult"))
+            .ok_or_else(|| missing_result(&document, "GetBoardResult"))?;
+
+        Ok(StationBoard {
+            generated_at: time!(string, board, "generatedAt")?,
+            location: Location::parse_with(&board, string, mode).map_err(|e| e.at(&board))?,
+            filter_location: match decoded_text!(string, board, "filterLocationName") {
+                Ok(name) => Some(Location {
+                    name,
+                    crs: optional_identifier!(mode, string, board, "filtercrs")?,
+                    tiploc: optional_identifier!(mode, string, board, "filterTiploc")?,
+                }),
+                Err(_) => None,
+            },
+            filter_type: match text!(string, board, "filterType") {
+                Ok("to") => Some(FilterType::To),
+                Ok("from") => Some(FilterType::From),
+                Ok(x) => {
+                    return Err(ParsingError::InvalidField {
+                        field: "filterType",
+                        expected: "to or from",
+                        found: Some(x.into()),
+                    })
+                }
+                Err(_) => None,
+            },
+            platforms_hidden: bool!(string, board, "platformsAreHidden", false)?,
+            services_unavailable: bool!(string, board, "servicesAreUnavailable", false)?,
+            truncated: bool!(string, board, "isTruncated", false)?,
+            train_services: services(child!(board, "trainServices").ok(), string, mode)?,
+            bus_services: services(child!(board, "busServices").ok(), string, mode)?,
+            ferry_services: services(child!(board, "ferryServices").ok(), string, mode)?,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Resolves the cancellation and delay reasons of every service on this board into text.
+    pub fn resolve_reasons(&mut self, catalogue: &ReasonCatalogue) {
+        let services = [
+            &mut self.train_services,
+            &mut self.bus_services,
+            &mut self.ferry_services,
+        ];
+
+        for service in services.into_iter().flatten() {
+            service.resolve_reasons(catalogue);
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for StationBoard<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<StationBoard<'a>, ParsingError<'a>> {
+        StationBoard::parse_with(string, &mut Mode::Strict)
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for ServiceItem<'a> {
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let time = &self.time;
+
+        let scheduled = if options.working {
+            time.working_scheduled()
+        } else {
+            self.effective_scheduled()
+        };
+        let actual = time.effective_actual();
+
+        let colour = match time.lateness() {
+            _ if self.cancelled => CANCELLED,
+            Some(UserLateness::Late(_)) | Some(UserLateness::Delayed) => LATE,
+            _ if actual.is_some() => PASSED,
+            _ => SCHEDULED,
+        };
+
+        let names = |locations: &[Location]| {
+            locations
+                .iter()
+                .map(|location| &*location.name)
+                .collect::<Vec<&str>>()
+                .join(" & ")
+        };
+
+        let strings: &[ANSIString] = &[
+            Style::default().paint(INDENT),
+            Fixed(LIGHT_SCHEDULED).paint(format_scheduled(scheduled, options.working)),
+            Style::default().paint(" "),
+            Fixed(colour).paint(match actual {
+                _ if self.cancelled => "canc.".to_string(),
+                Some(actual) => actual.format("%H:%M").to_string(),
+                None => "     ".to_string(),
+            }),
+            Style::default().paint(" "),
+            Fixed(GREY).paint(match self.platform {
+                _ if self.pass => "pass".to_string(),
+                Some(platform) if !self.platform_hidden => format!("{:>4}", platform),
+                _ => "   -".to_string(),
+            }),
+            Style::default().paint(" "),
+            Fixed(PURPLE).bold().paint(&*self.trainid),
+            Style::default().paint(" "),
+            Fixed(GREY).paint(names(&self.origin)),
+            Fixed(GREY).paint(" to "),
+            Style::default().bold().paint(names(&self.destination)),
+            Fixed(GREY).paint(format!(" ({})", self.operator_code)),
+            Fixed(GREY).paint(if self.passenger_service {
+                String::new()
+            } else {
+                format!(" [{}]", self.category)
+            }),
+            Fixed(GREY).paint(if self.operational {
+                " operational\n"
+            } else {
+                "\n"
+            }),
+        ];
+
+        ANSIStrings(strings).to_string()
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for StationBoard<'a> {
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let mut strings: Vec<ANSIString> = vec![
+            Style::default().paint("Board for "),
+            Style::default().bold().paint(&*self.location.name),
+        ];
+
+        if let Some(code) = self
+            .location
+            .crs
+            .as_deref()
+            .or(self.location.tiploc.as_deref())
+        {
+            strings.push(Fixed(GREY).paint(format!(" ({})", code)));
+        }
+
+        if let Some(filter) = &self.filter_location {
+            strings.push(Fixed(GREY).paint(match self.filter_type {
+                Some(FilterType::From) => "\nFrom ",
+                _ => "\nCalling at ",
+            }));
+            strings.push(Fixed(PURPLE).bold().paint(&*filter.name));
+        }
+
+        strings.push(Fixed(GREY).paint("\nGenerated at "));
+        strings.push(
+            Fixed(PURPLE)
+                .bold()
+                .paint(self.generated_at.format("%H:%M").to_string()),
+        );
+        strings.push(Style::default().paint("\n\n"));
+
+        let mut ret = ANSIStrings(&strings).to_string();
+
+        for service in self
+            .train_services
+            .iter()
+            .chain(&self.bus_services)
+            .chain(&self.ferry_services)
+        {
+            ret.push_str(&service.pretty_with(options));
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,442 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The kind of vehicle a service runs with.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ServiceType {
+    /// A train.
+    #[default]
+    Train,
+    /// A bus, such as a rail replacement bus.
+    Bus,
+    /// A ferry.
+    Ferry,
+}
+
+impl Display for ServiceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ServiceType::Train => "train",
+            ServiceType::Bus => "bus",
+            ServiceType::Ferry => "ferry",
+        })
+    }
+}
+
+/// An unknown service type was given.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown service type {0}, expected train, bus or ferry")]
+pub struct UnknownServiceType(pub String);
+
+impl FromStr for ServiceType {
+    type Err = UnknownServiceType;
+
+    /// Parses a service type as it appears in responses, such as "train".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "train" => Ok(ServiceType::Train),
+            "bus" => Ok(ServiceType::Bus),
+            "ferry" => Ok(ServiceType::Ferry),
+            _ => Err(UnknownServiceType(s.to_string())),
+        }
+    }
+}
+
+/// The category of a service, from the CIF.
+///
+/// See [CIF Codes](https://wiki.openraildata.com/index.php?title=CIF_Codes) on the Open Rail Data Wiki.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TrainCategory {
+    // Ordinary passenger.
+    /// London Underground/Metro Service. (OL)
+    LondonUndergroundOrMetro,
+    /// Unadvertised Ordinary Passenger. (OU)
+    UnadvertisedOrdinaryPassenger,
+    /// Ordinary Passenger. (OO)
+    OrdinaryPassenger,
+    /// Staff Train. (OS)
+    StaffTrain,
+
+    // Express passenger.
+    /// Channel Tunnel. (XC)
+    ChannelTunnel,
+    /// Sleeper. (XD)
+    Sleeper,
+    /// International. (XI)
+    International,
+    /// Motorail. (XR)
+    Motorail,
+    /// Unadvertised Express. (XU)
+    UnadvertisedExpress,
+    /// Express Passenger. (XX)
+    ExpressPassenger,
+    /// Sleeper (Domestic). (XZ)
+    DomesticSleeper,
+
+    // Buses and ships.
+    /// Rail replacement bus. (BR)
+    ReplacementBus,
+    /// Bus. (BS)
+    Bus,
+    /// Ship. (SS)
+    Ship,
+
+    // Empty coaching stock.
+    /// Empty Coaching Stock (ECS). (EE)
+    EmptyCoachingStock,
+    /// ECS, London Underground/Metro Service. (EL)
+    EmptyCoachingStockLondonUndergroundOrMetro,
+    /// ECS and Staff. (ES)
+    EmptyCoachingStockAndStaff,
+
+    // Parcels and postal.
+    /// Postal. (JJ)
+    Postal,
+    /// Post Office Controlled Parcels. (PM)
+    PostOfficeParcels,
+    /// Parcels. (PP)
+    Parcels,
+    /// Empty NPCCS. (PV)
+    EmptyNPCCS,
+
+    // Departmental.
+    /// Departmental. (DD)
+    Departmental,
+    /// Civil Engineer. (DH)
+    CivilEngineer,
+    /// Mechanical & Electrical Engineer. (DI)
+    MechanicalAndElectricalEngineer,
+    /// Stores. (DQ)
+    Stores,
+    /// Test. (DT)
+    Test,
+    /// Signal & Telecommunications Engineer. (DY)
+    SignalAndTelecommunicationsEngineer,
+
+    // Light locomotives.
+    /// Locomotive & Brake Van. (ZB)
+    LocomotiveAndBrakeVan,
+    /// Light Locomotive. (ZZ)
+    LightLocomotive,
+
+    // Railfreight Distribution.
+    /// RfD Automotive (Components). (J2)
+    AutomotiveComponents,
+    /// RfD Automotive (Vehicles). (H2)
+    AutomotiveVehicles,
+    /// RfD Edible Products (UK Contracts). (J3)
+    EdibleProducts,
+    /// RfD Industrial Minerals (UK Contracts). (J4)
+    IndustrialMinerals,
+    /// RfD Chemicals (UK Contracts). (J5)
+    Chemicals,
+    /// RfD Building Materials (UK Contracts). (J6)
+    BuildingMaterials,
+    /// RfD General Merchandise (UK Contracts). (J8)
+    GeneralMerchandise,
+    /// RfD European. (H8)
+    European,
+    /// RfD Freightliner (Contracts). (J9)
+    FreightlinerContracts,
+    /// RfD Freightliner (Other). (H9)
+    FreightlinerOther,
+
+    // Trainload freight.
+    /// Coal (Distributive). (A0)
+    CoalDistributive,
+    /// Coal (Electricity) MGR. (E0)
+    CoalElectricity,
+    /// Coal (Other) and Nuclear. (B0)
+    CoalOtherAndNuclear,
+    /// Metals. (B1)
+    Metals,
+    /// Aggregates. (B4)
+    Aggregates,
+    /// Domestic and Industrial Waste. (B5)
+    Waste,
+    /// Building Materials (TLF). (B6)
+    BuildingMaterialsTLF,
+    /// Petroleum Products. (B7)
+    PetroleumProducts,
+
+    // Railfreight Distribution (Channel Tunnel).
+    /// RfD European Channel Tunnel (Mixed Business). (H0)
+    ChannelTunnelMixedBusiness,
+    /// RfD European Channel Tunnel Intermodal. (H1)
+    ChannelTunnelIntermodal,
+    /// RfD European Channel Tunnel Automotive. (H3)
+    ChannelTunnelAutomotive,
+    /// RfD European Channel Tunnel Contract Services. (H4)
+    ChannelTunnelContractServices,
+    /// RfD European Channel Tunnel Haulmark. (H5)
+    ChannelTunnelHaulmark,
+    /// RfD European Channel Tunnel Joint Venture. (H6)
+    ChannelTunnelJointVenture,
+    /// A category not in the CIF specification. The string is the code that was found.
+    Unknown(String),
+}
+
+impl TrainCategory {
+    /// Gets the category of a CIF code, such as "XX". Unknown codes give `Unknown`, so this never fails.
+    pub fn from_code(code: &str) -> TrainCategory {
+        match code {
+            "OL" => TrainCategory::LondonUndergroundOrMetro,
+            "OU" => TrainCategory::UnadvertisedOrdinaryPassenger,
+            "OO" => TrainCategory::OrdinaryPassenger,
+            "OS" => TrainCategory::StaffTrain,
+            "XC" => TrainCategory::ChannelTunnel,
+            "XD" => TrainCategory::Sleeper,
+            "XI" => TrainCategory::International,
+            "XR" => TrainCategory::Motorail,
+            "XU" => TrainCategory::UnadvertisedExpress,
+            "XX" => TrainCategory::ExpressPassenger,
+            "XZ" => TrainCategory::DomesticSleeper,
+            "BR" => TrainCategory::ReplacementBus,
+            "BS" => TrainCategory::Bus,
+            "SS" => TrainCategory::Ship,
+            "EE" => TrainCategory::EmptyCoachingStock,
+            "EL" => TrainCategory::EmptyCoachingStockLondonUndergroundOrMetro,
+            "ES" => TrainCategory::EmptyCoachingStockAndStaff,
+            "JJ" => TrainCategory::Postal,
+            "PM" => TrainCategory::PostOfficeParcels,
+            "PP" => TrainCategory::Parcels,
+            "PV" => TrainCategory::EmptyNPCCS,
+            "DD" => TrainCategory::Departmental,
+            "DH" => TrainCategory::CivilEngineer,
+            "DI" => TrainCategory::MechanicalAndElectricalEngineer,
+            "DQ" => TrainCategory::Stores,
+            "DT" => TrainCategory::Test,
+            "DY" => TrainCategory::SignalAndTelecommunicationsEngineer,
+            "ZB" => TrainCategory::LocomotiveAndBrakeVan,
+            "ZZ" => TrainCategory::LightLocomotive,
+            "J2" => TrainCategory::AutomotiveComponents,
+            "H2" => TrainCategory::AutomotiveVehicles,
+            "J3" => TrainCategory::EdibleProducts,
+            "J4" => TrainCategory::IndustrialMinerals,
+            "J5" => TrainCategory::Chemicals,
+            "J6" => TrainCategory::BuildingMaterials,
+            "J8" => TrainCategory::GeneralMerchandise,
+            "H8" => TrainCategory::European,
+            "J9" => TrainCategory::FreightlinerContracts,
+            "H9" => TrainCategory::FreightlinerOther,
+            "A0" => TrainCategory::CoalDistributive,
+            "E0" => TrainCategory::CoalElectricity,
+            "B0" => TrainCategory::CoalOtherAndNuclear,
+            "B1" => TrainCategory::Metals,
+            "B4" => TrainCategory::Aggregates,
+            "B5" => TrainCategory::Waste,
+            "B6" => TrainCategory::BuildingMaterialsTLF,
+            "B7" => TrainCategory::PetroleumProducts,
+            "H0" => TrainCategory::ChannelTunnelMixedBusiness,
+            "H1" => TrainCategory::ChannelTunnelIntermodal,
+            "H3" => TrainCategory::ChannelTunnelAutomotive,
+            "H4" => TrainCategory::ChannelTunnelContractServices,
+            "H5" => TrainCategory::ChannelTunnelHaulmark,
+            "H6" => TrainCategory::ChannelTunnelJointVenture,
+            x => TrainCategory::Unknown(x.to_string()),
+        }
+    }
+
+    /// The CIF code of this category, such as "XX".
+    pub fn code(&self) -> &str {
+        match self {
+            TrainCategory::LondonUndergroundOrMetro => "OL",
+            TrainCategory::UnadvertisedOrdinaryPassenger => "OU",
+            TrainCategory::OrdinaryPassenger => "OO",
+            TrainCategory::StaffTrain => "OS",
+            TrainCategory::ChannelTunnel => "XC",
+            TrainCategory::Sleeper => "XD",
+            TrainCategory::International => "XI",
+            TrainCategory::Motorail => "XR",
+            TrainCategory::UnadvertisedExpress => "XU",
+            TrainCategory::ExpressPassenger => "XX",
+            TrainCategory::DomesticSleeper => "XZ",
+            TrainCategory::ReplacementBus => "BR",
+            TrainCategory::Bus => "BS",
+            TrainCategory::Ship => "SS",
+            TrainCategory::EmptyCoachingStock => "EE",
+            TrainCategory::EmptyCoachingStockLondonUndergroundOrMetro => "EL",
+            TrainCategory::EmptyCoachingStockAndStaff => "ES",
+            TrainCategory::Postal => "JJ",
+            TrainCategory::PostOfficeParcels => "PM",
+            TrainCategory::Parcels => "PP",
+            TrainCategory::EmptyNPCCS => "PV",
+            TrainCategory::Departmental => "DD",
+            TrainCategory::CivilEngineer => "DH",
+            TrainCategory::MechanicalAndElectricalEngineer => "DI",
+            TrainCategory::Stores => "DQ",
+            TrainCategory::Test => "DT",
+            TrainCategory::SignalAndTelecommunicationsEngineer => "DY",
+            TrainCategory::LocomotiveAndBrakeVan => "ZB",
+            TrainCategory::LightLocomotive => "ZZ",
+            TrainCategory::AutomotiveComponents => "J2",
+            TrainCategory::AutomotiveVehicles => "H2",
+            TrainCategory::EdibleProducts => "J3",
+            TrainCategory::IndustrialMinerals => "J4",
+            TrainCategory::Chemicals => "J5",
+            TrainCategory::BuildingMaterials => "J6",
+            TrainCategory::GeneralMerchandise => "J8",
+            TrainCategory::European => "H8",
+            TrainCategory::FreightlinerContracts => "J9",
+            TrainCategory::FreightlinerOther => "H9",
+            TrainCategory::CoalDistributive => "A0",
+            TrainCategory::CoalElectricity => "E0",
+            TrainCategory::CoalOtherAndNuclear => "B0",
+            TrainCategory::Metals => "B1",
+            TrainCategory::Aggregates => "B4",
+            TrainCategory::Waste => "B5",
+            TrainCategory::BuildingMaterialsTLF => "B6",
+            TrainCategory::PetroleumProducts => "B7",
+            TrainCategory::ChannelTunnelMixedBusiness => "H0",
+            TrainCategory::ChannelTunnelIntermodal => "H1",
+            TrainCategory::ChannelTunnelAutomotive => "H3",
+            TrainCategory::ChannelTunnelContractServices => "H4",
+            TrainCategory::ChannelTunnelHaulmark => "H5",
+            TrainCategory::ChannelTunnelJointVenture => "H6",
+            TrainCategory::Unknown(code) => code,
+        }
+    }
+
+    /// A short description of this category, such as "Express Passenger".
+    pub fn description(&self) -> &'static str {
+        match self {
+            TrainCategory::LondonUndergroundOrMetro => "London Underground/Metro Service",
+            TrainCategory::UnadvertisedOrdinaryPassenger => "Unadvertised Ordinary Passenger",
+            TrainCategory::OrdinaryPassenger => "Ordinary Passenger",
+            TrainCategory::StaffTrain => "Staff Train",
+            TrainCategory::ChannelTunnel => "Channel Tunnel",
+            TrainCategory::Sleeper => "Sleeper",
+            TrainCategory::International => "International",
+            TrainCategory::Motorail => "Motorail",
+            TrainCategory::UnadvertisedExpress => "Unadvertised Express",
+            TrainCategory::ExpressPassenger => "Express Passenger",
+            TrainCategory::DomesticSleeper => "Sleeper (Domestic)",
+            TrainCategory::ReplacementBus => "Rail replacement bus",
+            TrainCategory::Bus => "Bus",
+            TrainCategory::Ship => "Ship",
+            TrainCategory::EmptyCoachingStock => "Empty Coaching Stock (ECS)",
+            TrainCategory::EmptyCoachingStockLondonUndergroundOrMetro => {
+                "ECS, London Underground/Metro Service"
+            }
+            TrainCategory::EmptyCoachingStockAndStaff => "ECS and Staff",
+            TrainCategory::Postal => "Postal",
+            TrainCategory::PostOfficeParcels => "Post Office Controlled Parcels",
+            TrainCategory::Parcels => "Parcels",
+            TrainCategory::EmptyNPCCS => "Empty NPCCS",
+            TrainCategory::Departmental => "Departmental",
+            TrainCategory::CivilEngineer => "Civil Engineer",
+            TrainCategory::MechanicalAndElectricalEngineer => "Mechanical & Electrical Engineer",
+            TrainCategory::Stores => "Stores",
+            TrainCategory::Test => "Test",
+            TrainCategory::SignalAndTelecommunicationsEngineer => {
+                "Signal & Telecommunications Engineer"
+            }
+            TrainCategory::LocomotiveAndBrakeVan => "Locomotive & Brake Van",
+            TrainCategory::LightLocomotive => "Light Locomotive",
+            TrainCategory::AutomotiveComponents => "RfD Automotive (Components)",
+            TrainCategory::AutomotiveVehicles => "RfD Automotive (Vehicles)",
+            TrainCategory::EdibleProducts => "RfD Edible Products (UK Contracts)",
+            TrainCategory::IndustrialMinerals => "RfD Industrial Minerals (UK Contracts)",
+            TrainCategory::Chemicals => "RfD Chemicals (UK Contracts)",
+            TrainCategory::BuildingMaterials => "RfD Building Materials (UK Contracts)",
+            TrainCategory::GeneralMerchandise => "RfD General Merchandise (UK Contracts)",
+            TrainCategory::European => "RfD European",
+            TrainCategory::FreightlinerContracts => "RfD Freightliner (Contracts)",
+            TrainCategory::FreightlinerOther => "RfD Freightliner (Other)",
+            TrainCategory::CoalDistributive => "Coal (Distributive)",
+            TrainCategory::CoalElectricity => "Coal (Electricity) MGR",
+            TrainCategory::CoalOtherAndNuclear => "Coal (Other) and Nuclear",
+            TrainCategory::Metals => "Metals",
+            TrainCategory::Aggregates => "Aggregates",
+            TrainCategory::Waste => "Domestic and Industrial Waste",
+            TrainCategory::BuildingMaterialsTLF => "Building Materials (TLF)",
+            TrainCategory::PetroleumProducts => "Petroleum Products",
+            TrainCategory::ChannelTunnelMixedBusiness => {
+                "RfD European Channel Tunnel (Mixed Business)"
+            }
+            TrainCategory::ChannelTunnelIntermodal => "RfD European Channel Tunnel Intermodal",
+            TrainCategory::ChannelTunnelAutomotive => "RfD European Channel Tunnel Automotive",
+            TrainCategory::ChannelTunnelContractServices => {
+                "RfD European Channel Tunnel Contract Services"
+            }
+            TrainCategory::ChannelTunnelHaulmark => "RfD European Channel Tunnel Haulmark",
+            TrainCategory::ChannelTunnelJointVenture => "RfD European Channel Tunnel Joint Venture",
+            TrainCategory::Unknown(_) => "unknown",
+        }
+    }
+
+    /// If true, this category carries passengers, including unadvertised services, buses and ships.
+    pub fn is_passenger(&self) -> bool {
+        matches!(
+            self,
+            TrainCategory::LondonUndergroundOrMetro
+                | TrainCategory::UnadvertisedOrdinaryPassenger
+                | TrainCategory::OrdinaryPassenger
+                | TrainCategory::ChannelTunnel
+                | TrainCategory::Sleeper
+                | TrainCategory::International
+                | TrainCategory::Motorail
+                | TrainCategory::UnadvertisedExpress
+                | TrainCategory::ExpressPassenger
+                | TrainCategory::DomesticSleeper
+                | TrainCategory::ReplacementBus
+                | TrainCategory::Bus
+                | TrainCategory::Ship
+        )
+    }
+
+    /// If true, this category is a bus rather than a train.
+    pub fn is_bus(&self) -> bool {
+        matches!(self, TrainCategory::ReplacementBus | TrainCategory::Bus)
+    }
+
+    /// If true, this category carries freight.
+    pub fn is_freight(&self) -> bool {
+        matches!(
+            self,
+            TrainCategory::AutomotiveComponents
+                | TrainCategory::AutomotiveVehicles
+                | TrainCategory::EdibleProducts
+                | TrainCategory::IndustrialMinerals
+                | TrainCategory::Chemicals
+                | TrainCategory::BuildingMaterials
+                | TrainCategory::GeneralMerchandise
+                | TrainCategory::European
+                | TrainCategory::FreightlinerContracts
+                | TrainCategory::FreightlinerOther
+                | TrainCategory::CoalDistributive
+                | TrainCategory::CoalElectricity
+                | TrainCategory::CoalOtherAndNuclear
+                | TrainCategory::Metals
+                | TrainCategory::Aggregates
+                | TrainCategory::Waste
+                | TrainCategory::BuildingMaterialsTLF
+                | TrainCategory::PetroleumProducts
+                | TrainCategory::ChannelTunnelMixedBusiness
+                | TrainCategory::ChannelTunnelIntermodal
+                | TrainCategory::ChannelTunnelAutomotive
+                | TrainCategory::ChannelTunnelContractServices
+                | TrainCategory::ChannelTunnelHaulmark
+                | TrainCategory::ChannelTunnelJointVenture
+        )
+    }
+}
+
+impl From<&str> for TrainCategory {
+    fn from(code: &str) -> Self {
+        TrainCategory::from_code(code)
+    }
+}
+
+impl Display for TrainCategory {
+    /// Writes the CIF code of this category.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.code())
+    }
+}
@@ -0,0 +1,539 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use reqwest::Client;
+use thiserror::Error;
+
+use crate::boards::{BoardOptions, StationBoard};
+use crate::departures::DeparturesBoard;
+use crate::disruptions::DisruptionList;
+use crate::fault::ApiError;
+use crate::history::Timeline;
+use crate::parsable::ParsingError;
+use crate::query::{QueryOptions, ServiceList};
+use crate::reasons::{ReasonCode, ReasonCodeList};
+use crate::reference::{StationList, TocList};
+use crate::request::{
+    BoardOperation, BoardRequest, DeparturesOperation, DeparturesRequest, DisruptionListRequest,
+    HistoricServiceDetailsRequest, HistoricTimelineRequest, QueryServicesRequest,
+    ReasonCodeListRequest, ReasonCodeRequest, Service, ServiceDetailsRequest, SoapRequest,
+    SourceInstanceNamesRequest, StationListRequest, TocListRequest,
+};
+use crate::services::ServiceDetails;
+use crate::sources::SourceInstanceList;
+use crate::version::ApiVersion;
+
+/// The default OpenLDBSVWS endpoint, for the default `ApiVersion`.
+pub const DEFAULT_ENDPOINT: &str =
+    "https://lite.realtime.nationalrail.co.uk/OpenLDBSVWS/ldbsv13.asmx";
+
+/// The default OpenLDBSVWS reference data endpoint.
+pub const DEFAULT_REFERENCE_ENDPOINT: &str =
+    "https://lite.realtime.nationalrail.co.uk/OpenLDBSVWS/ldbsvref.asmx";
+
+/// A fetch error.
+/// This describes an error that occurred while making a request to OpenLDBSVWS.
+#[derive(Error, Debug)]
+pub enum FetchError {
+    /// An error returned by the server.
+    #[error("server responded with error {error:?}")]
+    StatusError { error: u16 },
+    /// An error while sending the request.
+    #[error("couldn't send request")]
+    RequestError { source: reqwest::Error },
+    /// An error while parsing the XML document into a struct.
+    #[error("couldn't parse: {error}")]
+    ParseError { error: ParsingError<'static> },
+    /// An error while parsing the response into an XML document.
+    #[error("malformed XML document")]
+    ParseXMLError { source: roxmltree::Error },
+    /// The request was not sent, as it is invalid.
+    #[error("invalid request: {0}")]
+    InvalidRequest(&'static str),
+    /// The API returned an error, such as a rejected token.
+    #[error(transparent)]
+    Api(ApiError),
+}
+
+impl<'a> From<ParsingError<'a>> for FetchError {
+    fn from(error: ParsingError<'a>) -> Self {
+        match error {
+            ParsingError::XMLParseError { source } => FetchError::ParseXMLError { source },
+            ParsingError::Api(error) => FetchError::Api(error),
+            error => FetchError::ParseError {
+                error: error.into_owned(),
+            },
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(source: reqwest::Error) -> Self {
+        FetchError::RequestError { source }
+    }
+}
+
+/// An OpenLDBSVWS client.
+///
+/// The client holds a token for the LDB Webservice (Staff Version) and the endpoint to send requests to. Cloning a
+/// client is cheap, as the underlying `reqwest::Client` is reference counted.
+///
+/// Responses are returned owned, so they can be kept after the request and sent between tasks. To parse a response
+/// without copying it, get its body with `send` and parse it with `TryFrom`.
+#[derive(Debug, Clone)]
+pub struct LdbsvClient {
+    token: String,
+    endpoint: String,
+    reference_endpoint: String,
+    timeout: Duration,
+    lenient: bool,
+    version: ApiVersion,
+    client: Client,
+}
+
+impl LdbsvClient {
+    /// Creates a client for the default endpoint.
+    pub fn new(token: impl Into<String>) -> Self {
+        LdbsvClient {
+            token: token.into(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            reference_endpoint: DEFAULT_REFERENCE_ENDPOINT.to_string(),
+            timeout: Duration::new(5, 0),
+            lenient: false,
+            version: ApiVersion::default(),
+            client: Client::new(),
+        }
+    }
+
+    /// Sends requests to `endpoint` rather than the default endpoint.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Uses `version` of the schema, sending requests to its default endpoint with its namespace. To use a different
+    /// endpoint, call `with_endpoint` afterwards.
+    pub fn with_version(mut self, version: ApiVersion) -> Self {
+        self.version = version;
+        self.endpoint = version.endpoint();
+        self
+    }
+
+    /// Sends reference data requests to `endpoint` rather than the default reference data endpoint.
+    pub fn with_reference_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.reference_endpoint = endpoint.into();
+        self
+    }
+
+    /// Sets the timeout for each request. Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// If true, service details, boards, departures, service lists and disruptions are parsed with their
+    /// `parse_lenient`, so problems which don't spoil the whole response, such as an invalid identifier, are collected
+    /// as warnings rather than failing the request. Defaults to false.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Uses an existing `reqwest::Client` to send requests.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The token used to authenticate requests.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// The endpoint requests are sent to.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The version of the schema requests are made with.
+    pub fn version(&self) -> ApiVersion {
+        self.version
+    }
+
+    /// The endpoint reference data requests are sent to.
+    pub fn reference_endpoint(&self) -> &str {
+        &self.reference_endpoint
+    }
+
+    /// Sends a request to the endpoint of its service and returns the response body. The request is validated first,
+    /// so invalid requests fail without being sent.
+    pub async fn send(&self, request: &impl SoapRequest) -> Result<String, FetchError> {
+        request.validate().map_err(FetchError::InvalidRequest)?;
+
+        let endpoint = match request.service() {
+            Service::Ldbsv => &self.endpoint,
+            Service::Reference => &self.reference_endpoint,
+        };
+
+        let res = self
+            .client
+            .post(endpoint)
+            .body(request.envelope_for(&self.token, self.version))
+            .timeout(self.timeout)
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .header("Accept", "text/xml")
+            .header("SOAPAction", request.soap_action())
+            .send()
+            .await?;
+
+        let status = res.status();
+
+        if !status.is_success() {
+            // Failed requests usually explain themselves with a SOAP fault.
+            let body = res.text().await.unwrap_or_default();
+
+            return Err(
+                match ApiError::from_response(status.as_u16(), &body, request.is_service_request())
+                {
+                    Some(error) => FetchError::Api(error),
+                    None => FetchError::StatusError {
+                        error: status.as_u16(),
+                    },
+                },
+            );
+        }
+
+        Ok(res.text().await?)
+    }
+
+    /// Parses service details, leniently if the client is set to.
+    fn service_details<'a>(&self, body: &'a str) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
+        if self.lenient {
+            ServiceDetails::parse_lenient(body)
+        } else {
+            ServiceDetails::try_from(body)
+        }
+    }
+
+    /// Gets the service details of a service given its RTTI ID.
+    pub async fn get_service_details(
+        &self,
+        rid: &str,
+    ) -> Result<ServiceDetails<'static>, FetchError> {
+        let body = self.send(&ServiceDetailsRequest::new(rid)).await?;
+
+        Ok(self.service_details(&body)?.into_owned())
+    }
+
+    /// Gets the details of a service given its RTTI ID, as they were at `historic`.
+    pub async fn get_historic_service_details(
+        &self,
+        rid: &str,
+        historic: &DateTime<FixedOffset>,
+    ) -> Result<ServiceDetails<'static>, FetchError> {
+        let body = self
+            .send(&HistoricServiceDetailsRequest::new(rid, *historic))
+            .await?;
+
+        Ok(self.service_details(&body)?.into_owned())
+    }
+
+    /// Gets the points in time at which a service changed, given its RTTI ID.
+    pub async fn get_historic_timeline(&self, rid: &str) -> Result<Timeline, FetchError> {
+        let body = self.send(&HistoricTimelineRequest::new(rid)).await?;
+
+        Ok(Timeline::try_from(body.as_str())?)
+    }
+
+    /// Walks the timeline of a service, getting its details at every point it changed, oldest first.
+    pub async fn get_historic_snapshots(
+        &self,
+        rid: &str,
+    ) -> Result<Vec<ServiceDetails<'static>>, FetchError> {
+        let timeline = self.get_historic_timeline(rid).await?;
+        let mut snapshots = Vec::with_capacity(timeline.entries.len());
+
+        for entry in &timeline.entries {
+            let body = self
+                .send(&HistoricServiceDetailsRequest::new(rid, entry.time))
+                .await?;
+
+            snapshots.push(self.service_details(&body)?.into_owned());
+        }
+
+        Ok(snapshots)
+    }
+
+    async fn get_board(
+        &self,
+        request: BoardRequest<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let body = self.send(&request).await?;
+
+        let board = if self.lenient {
+            StationBoard::parse_lenient(&body)?
+        } else {
+            StationBoard::try_from(body.as_str())?
+        };
+
+        Ok(board.into_owned())
+    }
+
+    /// Gets the arrival board of a station given its CRS code.
+    pub async fn get_arrival_board_by_crs(
+        &self,
+        crs: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request =
+            BoardRequest::new(BoardOperation::ArrivalByCrs, crs).with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the departure board of a station given its CRS code.
+    pub async fn get_departure_board_by_crs(
+        &self,
+        crs: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request =
+            BoardRequest::new(BoardOperation::DepartureByCrs, crs).with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the combined arrival and departure board of a station given its CRS code.
+    pub async fn get_arrival_departure_board_by_crs(
+        &self,
+        crs: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::ArrivalDepartureByCrs, crs)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the arrival board of a location given its TIPLOC. Unlike CRS codes, TIPLOCs also identify junctions,
+    /// yards and other locations which are not stations.
+    pub async fn get_arrival_board_by_tiploc(
+        &self,
+        tiploc: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::ArrivalByTiploc, tiploc)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the departure board of a location given its TIPLOC.
+    pub async fn get_departure_board_by_tiploc(
+        &self,
+        tiploc: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::DepartureByTiploc, tiploc)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the combined arrival and departure board of a location given its TIPLOC. Services passing the location
+    /// are included, with `ServiceItem::pass` set.
+    pub async fn get_arrival_departure_board_by_tiploc(
+        &self,
+        tiploc: &str,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::ArrivalDepartureByTiploc, tiploc)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the arrival board of a station given its CRS code, as it was at `historic`.
+    pub async fn get_historic_arrival_board(
+        &self,
+        crs: &str,
+        historic: &DateTime<FixedOffset>,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::HistoricArrival(*historic), crs)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    /// Gets the departure board of a station given its CRS code, as it was at `historic`.
+    pub async fn get_historic_departure_board(
+        &self,
+        crs: &str,
+        historic: &DateTime<FixedOffset>,
+        options: &BoardOptions<'_>,
+    ) -> Result<StationBoard<'static>, FetchError> {
+        let request = BoardRequest::new(BoardOperation::HistoricDeparture(*historic), crs)
+            .with_options(options.clone());
+
+        self.get_board(request).await
+    }
+
+    async fn get_departures(
+        &self,
+        request: DeparturesRequest<'_>,
+    ) -> Result<DeparturesBoard<'static>, FetchError> {
+        let body = self.send(&request).await?;
+
+        let board = if self.lenient {
+            DeparturesBoard::parse_lenient(&body)?
+        } else {
+            DeparturesBoard::try_from(body.as_str())?
+        };
+
+        Ok(board.into_owned())
+    }
+
+    /// Gets the next departure from a station to each of up to 25 destinations, given their CRS codes. Only the time
+    /// and non-passenger options are used.
+    pub async fn get_next_departures_by_crs(
+        &self,
+        crs: &str,
+        destinations: &[&str],
+        options: &BoardOptions<'_>,
+    ) -> Result<DeparturesBoard<'static>, FetchError> {
+        let request = DeparturesRequest::new(DeparturesOperation::Next, crs, destinations)
+            .with_options(options.clone());
+
+        self.get_departures(request).await
+    }
+
+    /// Gets the fastest departure from a station to each of up to 25 destinations, that is, the departure which
+    /// arrives first. Only the time and non-passenger options are used.
+    pub async fn get_fastest_departures_by_crs(
+        &self,
+        crs: &str,
+        destinations: &[&str],
+        options: &BoardOptions<'_>,
+    ) -> Result<DeparturesBoard<'static>, FetchError> {
+        let request = DeparturesRequest::new(DeparturesOperation::Fastest, crs, destinations)
+            .with_options(options.clone());
+
+        self.get_departures(request).await
+    }
+
+    /// Like `get_next_departures_by_crs`, but also gets the calling points of each departure.
+    pub async fn get_next_departures_with_details_by_crs(
+        &self,
+        crs: &str,
+        destinations: &[&str],
+        options: &BoardOptions<'_>,
+    ) -> Result<DeparturesBoard<'static>, FetchError> {
+        let request =
+            DeparturesRequest::new(DeparturesOperation::NextWithDetails, crs, destinations)
+                .with_options(options.clone());
+
+        self.get_departures(request).await
+    }
+
+    /// Like `get_fastest_departures_by_crs`, but also gets the calling points of each departure.
+    pub async fn get_fastest_departures_with_details_by_crs(
+        &self,
+        crs: &str,
+        destinations: &[&str],
+        options: &BoardOptions<'_>,
+    ) -> Result<DeparturesBoard<'static>, FetchError> {
+        let request =
+            DeparturesRequest::new(DeparturesOperation::FastestWithDetails, crs, destinations)
+                .with_options(options.clone());
+
+        self.get_departures(request).await
+    }
+
+    /// Finds services given their headcode or TSDB Train UID and Scheduled Departure Date. This is how to find the RID
+    /// of a service to request its details.
+    pub async fn query_services(
+        &self,
+        service_id: &str,
+        sdd: NaiveDate,
+        options: &QueryOptions<'_>,
+    ) -> Result<ServiceList<'static>, FetchError> {
+        let request = QueryServicesRequest::new(service_id, sdd).with_options(options.clone());
+
+        let body = self.send(&request).await?;
+
+        let list = if self.lenient {
+            ServiceList::parse_lenient(&body)?
+        } else {
+            ServiceList::try_from(body.as_str())?
+        };
+
+        Ok(list.into_owned())
+    }
+
+    /// Gets the NRCC messages for one or more stations, given their CRS codes.
+    pub async fn get_disruption_list(
+        &self,
+        crs: &[&str],
+    ) -> Result<DisruptionList<'static>, FetchError> {
+        let body = self.send(&DisruptionListRequest::new(crs)).await?;
+
+        let list = if self.lenient {
+            DisruptionList::parse_lenient(&body)?
+        } else {
+            DisruptionList::try_from(body.as_str())?
+        };
+
+        Ok(list.into_owned())
+    }
+
+    /// Gets the text of a reason code.
+    pub async fn get_reason_code(&self, code: u16) -> Result<ReasonCode<'static>, FetchError> {
+        let body = self.send(&ReasonCodeRequest::new(code)).await?;
+
+        Ok(ReasonCode::try_from(body.as_str())?.into_owned())
+    }
+
+    /// Gets the text of every reason code. The list can be turned into a `ReasonCatalogue` to resolve the reasons
+    /// given by services.
+    pub async fn get_reason_code_list(&self) -> Result<ReasonCodeList<'static>, FetchError> {
+        let body = self.send(&ReasonCodeListRequest).await?;
+
+        Ok(ReasonCodeList::try_from(body.as_str())?.into_owned())
+    }
+
+    /// Gets the names of every source instance, such as CIS and TD areas, which provide times for services.
+    pub async fn get_source_instance_names(
+        &self,
+    ) -> Result<SourceInstanceList<'static>, FetchError> {
+        let body = self.send(&SourceInstanceNamesRequest).await?;
+
+        Ok(SourceInstanceList::try_from(body.as_str())?.into_owned())
+    }
+
+    /// Gets every train operating company from the reference data endpoint. If `current_version` is the version of
+    /// the latest list, the returned list is empty.
+    pub async fn get_toc_list(
+        &self,
+        current_version: Option<&str>,
+    ) -> Result<TocList<'static>, FetchError> {
+        let request = TocListRequest::new().with_current_version(current_version);
+
+        let body = self.send(&request).await?;
+
+        Ok(TocList::try_from(body.as_str())?.into_owned())
+    }
+
+    /// Gets every station from the reference data endpoint. If `current_version` is the version of the latest list,
+    /// the returned list is empty.
+    pub async fn get_station_list(
+        &self,
+        current_version: Option<&str>,
+    ) -> Result<StationList<'static>, FetchError> {
+        let request = StationListRequest::new().with_current_version(current_version);
+
+        let body = self.send(&request).await?;
+
+        Ok(StationList::try_from(body.as_str())?.into_owned())
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use chrono::{DateTime, FixedOffset};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::boards::ServiceItem;
+use crate::identifiers::Crs;
+use crate::parsable::{missing_result, Mode, Parsable, ParsingError, ParsingWarning};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyOptions, PrettyPrintable, GREY, INDENT, PURPLE};
+use crate::services::{Location, ServiceLocation};
+use crate::{attr, bool, child, name, text, time};
+
+/// The maximum number of destinations that can be requested at once.
+pub const MAX_DESTINATIONS: usize = 25;
+
+/// The next (or fastest) departure to a destination.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct DepartureItem<'a> {
+    /// The CRS code of the destination.
+    pub crs: Crs<'a>,
+    /// The departure to the destination. If None, there is no departure to the destination within the time window.
+    pub service: Option<ServiceItem<'a>>,
+    /// The calling points of the departure. Only provided by the `WithDetails` requests.
+    pub calling_points: Option<Vec<ServiceLocation<'a>>>,
+}
+
+impl<'a> DepartureItem<'a> {
+    /// Copies any borrowed strings, so that this departure no longer borrows from the response.
+    pub fn into_owned(self) -> DepartureItem<'static> {
+        DepartureItem {
+            crs: self.crs.into_owned(),
+            service: self.service.map(ServiceItem::into_owned),
+            calling_points: self.calling_points.map(|locations| {
+                locations
+                    .into_iter()
+                    .map(ServiceLocation::into_owned)
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl<'b> DepartureItem<'b> {
+    /// Parses a departure. In lenient mode, invalid identifiers are kept or left out with a warning and broken calling
+    /// points are left out with a warning, as with `ServiceDetails::parse_lenient`.
+    pub(crate) fn parse_with<'a>(
+        destination: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<DepartureItem<'b>, ParsingError<'b>> {
+        if name!(destination) != "destination" {
+            return Err(ParsingError::InvalidTagName("destination"));
+        }
+
+        let service = child!(destination, "service").ok();
+
+        Ok(DepartureItem {
+            crs: mode.identifier(destination, "crs", attr!(string, destination, "crs")?)?,
+            service: match &service {
+                Some(service) => Some(
+                    ServiceItem::parse_with(service, string, mode).map_err(|e| e.at(service))?,
+                ),
+                None => None,
+            },
+            calling_points: match service.and_then(|service| child!(service, "locations").ok()) {
+                Some(locations) => {
+                    let mut vec = Vec::new();
+
+                    for node in locations.children().filter(|x| x.is_element()) {
+                        let location = ServiceLocation::parse_with(&node, string, mode);
+
+                        if let Some(location) = mode.recover(&node, location)? {
+                            vec.push(location)
+                        }
+                    }
+
+                    Some(vec)
+                }
+                None => None,
+            },
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for DepartureItem<'b> {
+    fn parse(
+        destination: &Node<'a, 'a>,
+        string: &'b str,
+    ) -> Result<DepartureItem<'b>, ParsingError<'b>> {
+        DepartureItem::parse_with(destination, string, &mut Mode::Strict)
+    }
+}
+
+/// A departures board, listing the next (or fastest) departure from a location to each of a list of destinations.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct DeparturesBoard<'a> {
+    /// The time this board was generated.
+    pub generated_at: DateTime<FixedOffset>,
+    /// The location of this board.
+    pub location: Location<'a>,
+    /// If true, platforms should not be displayed to the public at this location.
+    pub platforms_hidden: bool,
+    /// If true, services at this location are not available, so the board is empty.
+    pub services_unavailable: bool,
+    /// The departures, keyed by the CRS code of their destination.
+    pub departures: BTreeMap<Crs<'a>, DepartureItem<'a>>,
+    /// Problems found while parsing leniently with `parse_lenient`. Always empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<ParsingWarning>,
+}
+
+impl<'a> DeparturesBoard<'a> {
+    /// Copies any borrowed strings, so that this board no longer borrows from the response.
+    pub fn into_owned(self) -> DeparturesBoard<'static> {
+        DeparturesBoard {
+            generated_at: self.generated_at,
+            location: self.location.into_owned(),
+            platforms_hidden: self.platforms_hidden,
+            services_unavailable: self.services_unavailable,
+            departures: self
+                .departures
+                .into_iter()
+                .map(|(crs, departure)| (crs.into_owned(), departure.into_owned()))
+                .collect(),
+            warnings: self.warnings,
+        }
+    }
+
+    /// Parses a departures board leniently. Unlike `try_from`, a departure which can't be parsed is left out with a
+    /// warning in `warnings`, and invalid identifiers are kept as they are, or left out if optional, with a warning.
+    pub fn parse_lenient(string: &'a str) -> Result<DeparturesBoard<'a>, ParsingError<'a>> {
+        let mut warnings = Vec::new();
+        let mut board = DeparturesBoard::parse_with(string, &mut Mode::Lenient(&mut warnings))?;
+
+        board.warnings = warnings;
+
+        Ok(board)
+    }
+
+    /// Finds and parses the result of a response.
+    fn parse_with(
+        string: &'a str,
+        mode: &mut Mode,
+    ) -> Result<DeparturesBoard<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        // The name of the result differs between requests, but it always contains the departures.
+        let departures = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("departures"))
+            .ok_or_else(|| missing_result(&document, "departures"))?;
+
+        let board = departures
+            .parent_element()
+            .ok_or(ParsingError::MissingField("departures"))?;
+
+        Ok(DeparturesBoard {
+            generated_at: time!(string, board, "generatedAt")?,
+            location: Location::parse_with(&board, string, mode).map_err(|e| e.at(&board))?,
+            platforms_hidden: bool!(string, board, "platformsAreHidden", false)?,
+            services_unavailable: bool!(string, board, "servicesAreUnavailable", false)?,
+            departures: {
+                let mut map = BTreeMap::new();
+
+                for node in departures.children().filter(|x| x.is_element()) {
+                    let departure = DepartureItem::parse_with(&node, string, mode);
+
+                    if let Some(departure) = mode.recover(&node, departure)? {
+                        map.insert(departure.crs.clone(), departure);
+                    }
+                }
+
+                map
+            },
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DeparturesBoard<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<DeparturesBoard<'a>, ParsingError<'a>> {
+        DeparturesBoard::parse_with(string, &mut Mode::Strict)
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for DepartureItem<'a> {
+    fn pretty(&self) -> String {
+        let mut ret = match &self.service {
+            Some(service) => service.pretty(),
+            None => ANSIStrings(&[
+                Style::default().paint(INDENT),
+                Fixed(GREY).paint("no departures\n"),
+            ])
+            .to_string(),
+        };
+
+        for location in self.calling_points.iter().flatten() {
+            ret.push_str(INDENT);
+            ret.push_str(&location.pretty());
+        }
+
+        ret
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for DeparturesBoard<'a> {
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let mut strings: Vec<ANSIString> = vec![
+            Style::default().paint("Departures from "),
+            Style::default().bold().paint(&*self.location.name),
+        ];
+
+        if let Some(crs) = &self.location.crs {
+            strings.push(Fixed(GREY).paint(format!(" ({})", crs)));
+        }
+
+        strings.push(Fixed(GREY).paint("\nGenerated at "));
+        strings.push(
+            Fixed(PURPLE)
+                .bold()
+                .paint(self.generated_at.format("%H:%M").to_string()),
+        );
+        strings.push(Style::default().paint("\n"));
+
+        let mut ret = ANSIStrings(&strings).to_string();
+
+        for (crs, departure) in &self.departures {
+            ret.push_str(
+                &ANSIStrings(&[
+                    Style::default().paint("\nTo "),
+                    Fixed(PURPLE).bold().paint(options.station(crs)),
+                    Style::default().paint("\n"),
+                ])
+                .to_string(),
+            );
+            ret.push_str(&departure.pretty());
+        }
+
+        ret
+    }
+}
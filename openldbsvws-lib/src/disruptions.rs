@@ -0,0 +1,325 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::identifiers::Crs;
+use crate::parsable::{
+    decoded, missing_result, owned, slice, Mode, Parsable, ParsingError, ParsingWarning,
+};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyOptions, PrettyPrintable, CANCELLED, GREY, INDENT, LATE, PURPLE};
+use crate::{bool, child, name, text};
+
+/// NRCC message categories.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisruptionCategory {
+    /// Disruption to trains.
+    Train,
+    /// Disruption at a station, such as a closed entrance or broken lift.
+    Station,
+    /// Disruption to connecting services, such as buses or the Underground.
+    Connections,
+    /// Problems with the information systems.
+    System,
+    /// Anything else.
+    Misc,
+    /// Advance notice of disruption to trains.
+    PriorTrains,
+    /// Advance notice of other disruption.
+    PriorOther,
+}
+
+/// NRCC message severities.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DisruptionSeverity {
+    /// For information only.
+    Normal,
+    /// Minor disruption.
+    Minor,
+    /// Major disruption.
+    Major,
+    /// Severe disruption, where travel may not be possible.
+    Severe,
+}
+
+/// An NRCC station message.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct DisruptionMessage<'a> {
+    /// The ID of this message.
+    pub id: Cow<'a, str>,
+    /// The category of this message.
+    pub category: DisruptionCategory,
+    /// The severity of this message.
+    pub severity: DisruptionSeverity,
+    /// If true, this message should not be displayed to the public.
+    pub suppressed: bool,
+    /// The body of this message, as HTML. This may contain tags, such as links, and HTML entities. Use
+    /// `plain_message` for something that can be displayed in a terminal.
+    pub message: Cow<'a, str>,
+    /// The CRS codes of the stations this message applies to.
+    pub stations: Vec<Crs<'a>>,
+}
+
+impl<'a> DisruptionMessage<'a> {
+    /// Copies any borrowed strings, so that this message no longer borrows from the response.
+    pub fn into_owned(self) -> DisruptionMessage<'static> {
+        DisruptionMessage {
+            id: owned(self.id),
+            category: self.category,
+            severity: self.severity,
+            suppressed: self.suppressed,
+            message: owned(self.message),
+            stations: self.stations.into_iter().map(Crs::into_owned).collect(),
+        }
+    }
+
+    /// The body of this message, with HTML tags stripped and HTML entities decoded.
+    pub fn plain_message(&self) -> String {
+        html_escape::decode_html_entities(&strip_html(&self.message)).into_owned()
+    }
+}
+
+/// Strips HTML tags from `string`, keeping the text of links and collapsing whitespace.
+fn strip_html(string: &str) -> String {
+    let mut text = String::with_capacity(string.len());
+    let mut in_tag = false;
+
+    for char in string.chars() {
+        match char {
+            '<' => in_tag = true,
+            // Tags such as <p> and <br> separate words.
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            char if !in_tag => text.push(char),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Gets the HTML of a message body. The HTML is usually escaped inside the XML, in which case the body is just text,
+/// which is decoded. Otherwise it is inline XHTML, which is taken as it is, since its escapes are those of the HTML.
+fn html<'a>(node: &Node, string: &'a str) -> Cow<'a, str> {
+    match (node.first_child(), node.last_child()) {
+        (Some(text), _) if node.children().all(|x| x.is_text()) => {
+            decoded(slice(string, text.range()), text.text().unwrap_or(""))
+        }
+        (Some(first), Some(last)) => {
+            Cow::Borrowed(slice(string, first.range().start..last.range().end))
+        }
+        _ => Cow::Borrowed(""),
+    }
+}
+
+impl<'b> DisruptionMessage<'b> {
+    /// Parses a message. In lenient mode, an invalid station CRS code is left out with a warning.
+    pub(crate) fn parse_with<'a>(
+        message: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<Self, ParsingError<'b>> {
+        if name!(message) != "message" {
+            return Err(ParsingError::InvalidTagName("message"));
+        }
+
+        Ok(DisruptionMessage {
+            id: text!(string, message, "id")?.into(),
+            category: match text!(string, message, "category")? {
+                "Train" => DisruptionCategory::Train,
+                "Station" => DisruptionCategory::Station,
+                "Connections" => DisruptionCategory::Connections,
+                "System" => DisruptionCategory::System,
+                "Misc" => DisruptionCategory::Misc,
+                "PriorTrains" => DisruptionCategory::PriorTrains,
+                "PriorOther" => DisruptionCategory::PriorOther,
+                x => {
+                    return Err(ParsingError::InvalidField {
+                        field: "category",
+                        expected: "message category",
+                        found: Some(x.into()),
+                    })
+                }
+            },
+            severity: match text!(string, message, "severity")? {
+                "0" => DisruptionSeverity::Normal,
+                "1" => DisruptionSeverity::Minor,
+                "2" => DisruptionSeverity::Major,
+                "3" => DisruptionSeverity::Severe,
+                x => {
+                    return Err(ParsingError::InvalidField {
+                        field: "severity",
+                        expected: "0, 1, 2 or 3",
+                        found: Some(x.into()),
+                    })
+                }
+            },
+            suppressed: bool!(string, message, "isSuppressed", false)?,
+            message: html(&child!(message, "xhtmlMessage")?, string),
+            stations: match child!(message, "stations") {
+                Ok(stations) => {
+                    let mut vec = Vec::new();
+
+                    for node in stations.children().filter(|x| x.has_tag_name("crs")) {
+                        let crs = slice(string, node.first_child().map_or(0..0, |x| x.range()));
+
+                        if let Some(crs) = mode.optional_identifier(&node, "crs", Some(crs))? {
+                            vec.push(crs);
+                        }
+                    }
+
+                    vec
+                }
+                Err(_) => Vec::new(),
+            },
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for DisruptionMessage<'b> {
+    fn parse(message: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        DisruptionMessage::parse_with(message, string, &mut Mode::Strict)
+    }
+}
+
+/// The NRCC messages for a list of stations, as returned by `GetDisruptionList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct DisruptionList<'a> {
+    /// The messages.
+    pub messages: Vec<DisruptionMessage<'a>>,
+    /// Problems found while parsing leniently with `parse_lenient`. Always empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<ParsingWarning>,
+}
+
+impl<'a> DisruptionList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> DisruptionList<'static> {
+        DisruptionList {
+            messages: self
+                .messages
+                .into_iter()
+                .map(DisruptionMessage::into_owned)
+                .collect(),
+            warnings: self.warnings,
+        }
+    }
+
+    /// Parses a list of messages leniently. Unlike `try_from`, a message which can't be parsed or an invalid station
+    /// CRS code is left out with a warning in `warnings`.
+    pub fn parse_lenient(string: &'a str) -> Result<DisruptionList<'a>, ParsingError<'a>> {
+        let mut warnings = Vec::new();
+        let mut list = DisruptionList::parse_with(string, &mut Mode::Lenient(&mut warnings))?;
+
+        list.warnings = warnings;
+
+        Ok(list)
+    }
+
+    /// Finds and parses the result of a response.
+    fn parse_with(
+        string: &'a str,
+        mode: &mut Mode,
+    ) -> Result<DisruptionList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetDisruptionListResult"))
+            .ok_or_else(|| missing_result(&document, "GetDisruptionListResult"))?;
+
+        let mut messages = Vec::new();
+
+        for node in result.children().filter(|x| x.is_element()) {
+            let message = DisruptionMessage::parse_with(&node, string, mode);
+
+            if let Some(message) = mode.recover(&node, message)? {
+                messages.push(message)
+            }
+        }
+
+        Ok(DisruptionList {
+            messages,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DisruptionList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<DisruptionList<'a>, ParsingError<'a>> {
+        DisruptionList::parse_with(string, &mut Mode::Strict)
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for DisruptionMessage<'a> {
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let colour = match self.severity {
+            DisruptionSeverity::Normal | DisruptionSeverity::Minor => PURPLE,
+            DisruptionSeverity::Major => LATE,
+            DisruptionSeverity::Severe => CANCELLED,
+        };
+
+        let strings: &[ANSIString] = &[
+            Fixed(colour).bold().paint(format!("{:?}", self.category)),
+            Fixed(GREY).paint(format!(" {:?}", self.severity)),
+            Fixed(GREY).paint(if self.suppressed { " suppressed" } else { "" }),
+            Fixed(GREY).paint(if self.stations.is_empty() {
+                "\n".to_string()
+            } else {
+                let stations: Vec<String> = self
+                    .stations
+                    .iter()
+                    .map(|crs| options.station(crs))
+                    .collect();
+
+                format!(" ({})\n", stations.join(", "))
+            }),
+            Style::default().paint(INDENT),
+            Style::default().paint(self.plain_message()),
+            Style::default().paint("\n"),
+        ];
+
+        ANSIStrings(strings).to_string()
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for DisruptionList<'a> {
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        if self.messages.is_empty() {
+            return Fixed(GREY).paint("No messages\n").to_string();
+        }
+
+        self.messages
+            .iter()
+            .map(|message| message.pretty_with(options))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
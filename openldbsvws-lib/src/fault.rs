@@ -0,0 +1,132 @@
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A SOAP fault, which the API returns instead of a result when a request fails.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    /// The fault code, such as "soap:Client" or "soap:Server".
+    pub code: String,
+    /// The human readable description of the fault.
+    pub message: String,
+    /// The text of the fault's detail, if any.
+    pub detail: Option<String>,
+}
+
+impl Fault {
+    /// Finds the fault in a response, if it has one. Both SOAP 1.1 and SOAP 1.2 faults are understood.
+    pub(crate) fn find(document: &Document) -> Option<Fault> {
+        let fault = document
+            .root()
+            .descendants()
+            .find(|x| x.tag_name().name() == "Fault")?;
+
+        // SOAP 1.1 uses faultcode and faultstring, whereas SOAP 1.2 nests the value and text.
+        let text = |node: Option<Node>| {
+            node.map(|node| {
+                node.descendants()
+                    .filter(|x| x.is_text())
+                    .filter_map(|x| x.text())
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+            })
+        };
+        let child = |name: &str| fault.children().find(|x| x.tag_name().name() == name);
+
+        Some(Fault {
+            code: text(child("faultcode").or_else(|| child("Code"))).unwrap_or_default(),
+            message: text(child("faultstring").or_else(|| child("Reason"))).unwrap_or_default(),
+            detail: text(child("detail").or_else(|| child("Detail"))).filter(|x| !x.is_empty()),
+        })
+    }
+
+    /// Makes a fault for an HTTP error which came without one.
+    fn from_status(status: u16, message: &str) -> Fault {
+        Fault {
+            code: status.to_string(),
+            message: message.to_string(),
+            detail: None,
+        }
+    }
+
+    /// If true, the fault code blames the request rather than the server, as "soap:Client" does.
+    fn blames_client(&self) -> bool {
+        self.code.ends_with("Client") || self.code.ends_with("Sender")
+    }
+}
+
+/// An error returned by the API.
+///
+/// Errors are told apart by the HTTP status and the fault code, as the descriptions aren't documented and vary.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The token is missing, invalid or not allowed to use the API.
+    #[error("the token was rejected: {}", .0.message)]
+    InvalidToken(Fault),
+    /// The requested service, such as a RID, doesn't exist. Only requests for a single service give this.
+    #[error("the service was not found: {}", .0.message)]
+    ServiceNotFound(Fault),
+    /// Too many requests have been made with the token.
+    #[error("too many requests: {}", .0.message)]
+    RateLimited(Fault),
+    /// The request was rejected, for example because of an invalid CRS code.
+    #[error("the request was rejected: {}", .0.message)]
+    InvalidRequest(Fault),
+    /// The server failed to handle the request.
+    #[error("the server failed: {}", .0.message)]
+    ServerError(Fault),
+}
+
+impl ApiError {
+    /// The fault returned by the API.
+    pub fn fault(&self) -> &Fault {
+        match self {
+            ApiError::InvalidToken(fault)
+            | ApiError::ServiceNotFound(fault)
+            | ApiError::RateLimited(fault)
+            | ApiError::InvalidRequest(fault)
+            | ApiError::ServerError(fault) => fault,
+        }
+    }
+
+    /// Works out the error for an unsuccessful HTTP response, given its status code and body. `service` is whether
+    /// the request was for a single service, such as `GetServiceDetailsByRID`, in which case a fault blaming the
+    /// request means the service wasn't found. If None, the response doesn't say what went wrong.
+    pub fn from_response(status: u16, body: &str, service: bool) -> Option<ApiError> {
+        let fault = Document::parse(body)
+            .ok()
+            .and_then(|document| Fault::find(&document));
+
+        let or_status = |fault: Option<Fault>, message| {
+            fault.unwrap_or_else(|| Fault::from_status(status, message))
+        };
+
+        match (status, fault) {
+            (401 | 403, fault) => Some(ApiError::InvalidToken(or_status(fault, "Unauthorized"))),
+            (429, fault) => Some(ApiError::RateLimited(or_status(fault, "Too Many Requests"))),
+            (404, fault) if service => {
+                Some(ApiError::ServiceNotFound(or_status(fault, "Not Found")))
+            }
+            (_, Some(fault)) if service && fault.blames_client() => {
+                Some(ApiError::ServiceNotFound(fault))
+            }
+            (_, Some(fault)) => Some(fault.into()),
+            (_, None) => None,
+        }
+    }
+}
+
+impl From<Fault> for ApiError {
+    /// Classifies a fault by its code alone, for when there is no HTTP status to go on.
+    fn from(fault: Fault) -> Self {
+        if fault.blames_client() {
+            ApiError::InvalidRequest(fault)
+        } else {
+            ApiError::ServerError(fault)
+        }
+    }
+}
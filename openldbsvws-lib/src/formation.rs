@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use roxmltree::Node;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::parsable::{owned, Parsable, ParsingError};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyPrintable, CANCELLED, GREY, HERE, INDENT, LATE, LINE, PASSED};
+use crate::{attr, child, name, parse, text};
+
+/// How busy a coach or train is.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoadingCategory {
+    /// Plenty of seats available. Less than 30% loaded.
+    Quiet,
+    /// Some seats available. Less than 60% loaded.
+    Moderate,
+    /// Few seats available. Less than 90% loaded.
+    Busy,
+    /// Standing room only.
+    VeryBusy,
+}
+
+impl LoadingCategory {
+    /// Gets the category of a loading percentage.
+    pub fn from_percentage(percentage: u8) -> Self {
+        match percentage {
+            0..=29 => LoadingCategory::Quiet,
+            30..=59 => LoadingCategory::Moderate,
+            60..=89 => LoadingCategory::Busy,
+            _ => LoadingCategory::VeryBusy,
+        }
+    }
+}
+
+/// An end of a train.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainEnd {
+    /// The front of the train, where the first coach in the formation is.
+    Front,
+    /// The rear of the train.
+    Rear,
+}
+
+/// A coach in a train's formation.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Coach<'a> {
+    /// The number of this coach, as shown to passengers. This is usually a letter, such as "A".
+    pub number: Cow<'a, str>,
+    /// The class of this coach, such as "First", "Standard" or "Mixed".
+    pub class: Option<Cow<'a, str>>,
+    /// The type of toilet in this coach, such as "Standard" or "Accessible". If None, this coach has no toilet.
+    pub toilet: Option<Cow<'a, str>>,
+    /// The status of the toilet in this coach, such as "InService" or "NotInService".
+    pub toilet_status: Option<Cow<'a, str>>,
+    /// How full this coach is, as a percentage. If None, the loading is unknown.
+    pub loading: Option<u8>,
+}
+
+impl<'a> Coach<'a> {
+    /// How busy this coach is, if known.
+    pub fn loading_category(&self) -> Option<LoadingCategory> {
+        self.loading.map(LoadingCategory::from_percentage)
+    }
+
+    /// Copies any borrowed strings, so that this coach no longer borrows from the response.
+    pub fn into_owned(self) -> Coach<'static> {
+        Coach {
+            number: owned(self.number),
+            class: self.class.map(owned),
+            toilet: self.toilet.map(owned),
+            toilet_status: self.toilet_status.map(owned),
+            loading: self.loading,
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Coach<'b> {
+    fn parse(coach: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(coach) != "coach" {
+            return Err(ParsingError::InvalidTagName("coach"));
+        }
+
+        let toilet = child!(coach, "toilet").ok();
+
+        Ok(Coach {
+            number: attr!(string, coach, "number")?.into(),
+            class: text!(string, coach, "coachClass").ok().map(Cow::from),
+            toilet: match text!(string, coach, "toilet") {
+                Ok("") | Ok("None") | Err(_) => None,
+                Ok(toilet) => Some(toilet.into()),
+            },
+            toilet_status: toilet
+                .and_then(|toilet| attr!(string, toilet, "status").ok())
+                .map(Cow::from),
+            loading: parse!(string, coach, "loading", u8).ok(),
+        })
+    }
+}
+
+/// The formation of a train at a location.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Formation<'a> {
+    /// The coaches of the train, from the front.
+    pub coaches: Vec<Coach<'a>>,
+    /// How full the whole train is, as a percentage. If None, the loading is unknown.
+    pub loading: Option<u8>,
+}
+
+impl<'a> Formation<'a> {
+    /// How busy the whole train is, if known.
+    pub fn loading_category(&self) -> Option<LoadingCategory> {
+        self.loading.map(LoadingCategory::from_percentage)
+    }
+
+    /// Finds the quieter end of the train, for hints such as "quieter coaches at the rear". If None, the loading of
+    /// the coaches is unknown, or both ends are about as busy as each other.
+    pub fn quieter_end(&self) -> Option<TrainEnd> {
+        let half = self.coaches.len() / 2;
+
+        let average = |coaches: &[Coach]| {
+            let loadings: Vec<u32> = coaches
+                .iter()
+                .filter_map(|coach| coach.loading.map(u32::from))
+                .collect();
+
+            match loadings.len() {
+                0 => None,
+                len => Some(loadings.iter().sum::<u32>() / len as u32),
+            }
+        };
+
+        let front = average(&self.coaches[..half])?;
+        let rear = average(&self.coaches[self.coaches.len() - half..])?;
+
+        // Small differences aren't worth telling anyone about.
+        if front + 15 <= rear {
+            Some(TrainEnd::Front)
+        } else if rear + 15 <= front {
+            Some(TrainEnd::Rear)
+        } else {
+            None
+        }
+    }
+
+    /// Copies any borrowed strings, so that this formation no longer borrows from the response.
+    pub fn into_owned(self) -> Formation<'static> {
+        Formation {
+            coaches: self.coaches.into_iter().map(Coach::into_owned).collect(),
+            loading: self.loading,
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Formation<'b> {
+    /// Parses the formation of a location. The node is the `location`, as the train level loading is outside of the
+    /// `formation` element.
+    fn parse(location: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        let mut coaches = Vec::new();
+
+        if let Some(list) = child!(location, "formation")
+            .ok()
+            .and_then(|formation| child!(formation, "coaches").ok())
+        {
+            for node in list.children().filter(|x| x.is_element()) {
+                coaches.push(Coach::parse_located(&node, string)?)
+            }
+        }
+
+        Ok(Formation {
+            coaches,
+            loading: child!(location, "serviceLoading")
+                .ok()
+                .and_then(|loading| parse!(string, loading, "loadingPercentage", u8).ok()),
+        })
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for Formation<'a> {
+    fn pretty(&self) -> String {
+        let colour = |category: Option<LoadingCategory>| match category {
+            Some(LoadingCategory::Quiet) => HERE,
+            Some(LoadingCategory::Moderate) => PASSED,
+            Some(LoadingCategory::Busy) => LATE,
+            Some(LoadingCategory::VeryBusy) => CANCELLED,
+            None => GREY,
+        };
+
+        let mut strings: Vec<ANSIString> = vec![
+            Style::default().paint(INDENT),
+            Fixed(GREY).paint(LINE),
+            Style::default().paint(INDENT),
+        ];
+
+        for coach in &self.coaches {
+            strings.push(
+                Fixed(colour(coach.loading_category())).paint(match coach.loading {
+                    Some(loading) => format!("[{} {:>2}%]", coach.number, loading),
+                    None => format!("[{}]", coach.number),
+                }),
+            );
+        }
+
+        if let Some(loading) = self.loading {
+            strings
+                .push(Fixed(colour(self.loading_category())).paint(format!(" {}% full", loading)));
+        }
+
+        match self.quieter_end() {
+            Some(TrainEnd::Front) => strings.push(Fixed(GREY).paint(", quieter at the front")),
+            Some(TrainEnd::Rear) => strings.push(Fixed(GREY).paint(", quieter at the rear")),
+            None => {}
+        }
+
+        strings.push(Style::default().paint("\n"));
+
+        ANSIStrings(&strings).to_string()
+    }
+}
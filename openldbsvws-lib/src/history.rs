@@ -0,0 +1,94 @@
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use chrono::{DateTime, FixedOffset};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::parsable::{missing_result, slice, Parsable, ParsingError};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyPrintable, GREY, INDENT, LIGHT_SCHEDULED};
+
+/// A point in time at which a service changed. The historic details of the service at this time can be requested
+/// with `GetHistoricServiceDetails`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimelineEntry {
+    /// The time of the change.
+    pub time: DateTime<FixedOffset>,
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for TimelineEntry {
+    fn parse(entry: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        // Unlike most fields, the time is the text of the entry itself.
+        let text = match entry.first_child().filter(|x| x.is_text()) {
+            Some(text) => slice(string, text.range()),
+            None => "",
+        };
+
+        Ok(TimelineEntry {
+            time: DateTime::parse_from_rfc3339(text).map_err(|_| ParsingError::InvalidField {
+                field: "dateTime",
+                expected: "DateTime",
+                found: Some(text.into()),
+            })?,
+        })
+    }
+}
+
+/// The timeline of a service, as returned by `GetHistoricTimeLine`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct Timeline {
+    /// The points at which the service changed, oldest first.
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl<'a> TryFrom<&'a str> for Timeline {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<Timeline, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetHistoricTimeLineResult"))
+            .ok_or_else(|| missing_result(&document, "GetHistoricTimeLineResult"))?;
+
+        let mut entries = Vec::new();
+
+        for node in result.children().filter(|x| x.is_element()) {
+            entries.push(TimelineEntry::parse_located(&node, string)?)
+        }
+
+        // The order isn't documented, so don't rely on it.
+        entries.sort();
+
+        Ok(Timeline { entries })
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl PrettyPrintable for Timeline {
+    fn pretty(&self) -> String {
+        let mut ret = ANSIStrings(&[
+            Style::default().paint("Timeline"),
+            Fixed(GREY).paint(format!(" ({} changes)\n", self.entries.len())),
+        ])
+        .to_string();
+
+        for entry in &self.entries {
+            let strings: &[ANSIString] = &[
+                Style::default().paint(INDENT),
+                Fixed(LIGHT_SCHEDULED).paint(entry.time.format("%Y-%m-%d %H:%M:%S").to_string()),
+                Style::default().paint("\n"),
+            ];
+
+            ret.push_str(&ANSIStrings(strings).to_string());
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,257 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::parsable::owned;
+
+/// An identifier, such as a RID or CRS code, wasn't valid.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid {expected}, got {found:?}")]
+pub struct InvalidIdentifier {
+    /// What was expected, such as "RID of 15 digits".
+    pub expected: &'static str,
+    /// The string which was given.
+    pub found: String,
+}
+
+/// What the parsers need of an identifier, so that they can handle invalid identifiers in responses.
+pub(crate) trait Identifier<'a>: Sized {
+    /// What a valid identifier looks like, for errors.
+    const EXPECTED: &'static str;
+
+    /// Checks that `value` is valid, borrowing it if so.
+    fn checked(value: &'a str) -> Result<Self, InvalidIdentifier>;
+
+    /// Borrows `value` without checking it, for keeping an invalid identifier when parsing leniently.
+    fn unchecked(value: &'a str) -> Self;
+}
+
+/// Defines a validated identifier. Identifiers borrow from the response like other fields, and serialize as plain
+/// strings. Identifiers from responses parsed leniently may be invalid, so their accessors can't assume they are.
+macro_rules! define_identifier {
+    ($(#[$meta: meta])* $name: ident, $expected: literal, $valid: expr) => {
+        $(#[$meta])*
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<'a>(Cow<'a, str>);
+
+        impl<'a> $name<'a> {
+            /// Checks that `value` is valid, borrowing it if so.
+            pub fn new(value: &'a str) -> Result<Self, InvalidIdentifier> {
+                let valid: fn(&[u8]) -> bool = $valid;
+
+                if valid(value.as_bytes()) {
+                    Ok($name(Cow::Borrowed(value)))
+                } else {
+                    Err(InvalidIdentifier {
+                        expected: $expected,
+                        found: value.to_string(),
+                    })
+                }
+            }
+
+            /// The identifier as a string.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Copies the identifier if it is borrowed, so that it no longer borrows from the response.
+            pub fn into_owned(self) -> $name<'static> {
+                $name(owned(self.0))
+            }
+        }
+
+        impl<'a> Identifier<'a> for $name<'a> {
+            const EXPECTED: &'static str = $expected;
+
+            fn checked(value: &'a str) -> Result<Self, InvalidIdentifier> {
+                $name::new(value)
+            }
+
+            fn unchecked(value: &'a str) -> Self {
+                $name(Cow::Borrowed(value))
+            }
+        }
+
+        impl Deref for $name<'_> {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name<'_> {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.pad(&self.0)
+            }
+        }
+
+        impl FromStr for $name<'static> {
+            type Err = InvalidIdentifier;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::new(s).map($name::into_owned)
+            }
+        }
+
+        impl TryFrom<String> for $name<'_> {
+            type Error = InvalidIdentifier;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::new(&value)?;
+
+                Ok($name(Cow::Owned(value)))
+            }
+        }
+
+        impl From<$name<'_>> for String {
+            fn from(identifier: $name<'_>) -> String {
+                identifier.0.into_owned()
+            }
+        }
+
+        impl PartialEq<str> for $name<'_> {
+            fn eq(&self, other: &str) -> bool {
+                *self.0 == *other
+            }
+        }
+
+        impl PartialEq<&str> for $name<'_> {
+            fn eq(&self, other: &&str) -> bool {
+                *self.0 == **other
+            }
+        }
+    };
+}
+
+/// Whether `value` is `length` upper case letters or digits.
+fn alphanumeric(value: &[u8], length: std::ops::RangeInclusive<usize>) -> bool {
+    length.contains(&value.len())
+        && value
+            .iter()
+            .all(|x| x.is_ascii_uppercase() || x.is_ascii_digit())
+}
+
+define_identifier!(
+    /// An RTTI ID, which identifies a service on a day, such as "202209207612345". RIDs are the scheduled departure
+    /// date followed by a serial of seven digits.
+    Rid,
+    "RID of 15 digits, starting with a date",
+    |x| {
+        x.len() == 15
+            && x.iter().all(u8::is_ascii_digit)
+            && std::str::from_utf8(&x[..8])
+                .ok()
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+                .is_some()
+    }
+);
+
+impl Rid<'_> {
+    /// The date this service is scheduled to depart on. None if this RID is invalid, which is only possible if it
+    /// comes from a response parsed leniently.
+    pub fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.0.get(..8)?, "%Y%m%d").ok()
+    }
+
+    /// The serial of this service on its date, such as "7612345".
+    pub fn serial(&self) -> &str {
+        self.0.get(8..).unwrap_or("")
+    }
+}
+
+define_identifier!(
+    /// A TSDB train UID, which identifies a schedule, such as "P12345". UIDs are a letter followed by five letters or
+    /// digits.
+    Uid,
+    "UID of a letter and five letters or digits",
+    |x| alphanumeric(x, 6..=6) && x[0].is_ascii_uppercase()
+);
+
+define_identifier!(
+    /// A headcode, or train ID, such as "1A23". Headcodes are the class of the train, the area it is going to and a
+    /// serial. Buses and ferries have headcodes too, such as "0B00".
+    Headcode,
+    "headcode of a digit and three letters or digits",
+    |x| alphanumeric(x, 4..=4) && x[0].is_ascii_digit()
+);
+
+impl Headcode<'_> {
+    /// The class of this train, such as 1 for an express passenger train or 5 for empty coaching stock. None if this
+    /// headcode is invalid, which is only possible if it comes from a response parsed leniently.
+    pub fn class(&self) -> Option<u8> {
+        self.0
+            .as_bytes()
+            .first()
+            .filter(|x| x.is_ascii_digit())
+            .map(|x| x - b'0')
+    }
+
+    /// The area this train is going to, such as 'A'. Areas are letters for most trains, but may be digits. None if
+    /// this headcode is invalid, as with `class`.
+    pub fn destination_area(&self) -> Option<char> {
+        self.0.as_bytes().get(1).map(|x| char::from(*x))
+    }
+
+    /// The serial of this train, such as "23".
+    pub fn serial(&self) -> &str {
+        self.0.get(2..).unwrap_or("")
+    }
+}
+
+define_identifier!(
+    /// A retail service ID, such as "GW123400". RSIDs are the operator's code followed by a service number of four
+    /// digits and, optionally, two more digits for a portion of the service.
+    Rsid,
+    "RSID of two letters and four or six digits",
+    |x| {
+        (x.len() == 6 || x.len() == 8)
+            && x[..2].iter().all(u8::is_ascii_uppercase)
+            && x[2..].iter().all(u8::is_ascii_digit)
+    }
+);
+
+impl Rsid<'_> {
+    /// The code of the operator this RSID belongs to, such as "GW".
+    pub fn toc(&self) -> &str {
+        self.0.get(..2).unwrap_or("")
+    }
+
+    /// The service number, without the operator or portion, such as "1234".
+    pub fn service_number(&self) -> &str {
+        self.0.get(2..6).unwrap_or("")
+    }
+
+    /// The portion of the service, if any, such as "00".
+    pub fn portion(&self) -> Option<&str> {
+        self.0.get(6..).filter(|portion| !portion.is_empty())
+    }
+}
+
+define_identifier!(
+    /// A CRS code, which identifies a station, such as "KGX".
+    Crs,
+    "CRS code of three letters",
+    |x| x.len() == 3 && x.iter().all(u8::is_ascii_uppercase)
+);
+
+define_identifier!(
+    /// A TIPLOC, which identifies a timing point, such as "KNGX". TIPLOCs are up to seven letters or digits.
+    Tiploc,
+    "TIPLOC of up to seven letters or digits",
+    |x| alphanumeric(x, 1..=7)
+);
@@ -1,73 +1,60 @@
-pub use parsable::ParsingError;
-pub use services::ServiceDetails;
-
+pub use activities::{Activities, Activity, UnknownActivity};
+pub use associations::{Association, AssociationCategory};
+pub use boards::{
+    BoardOptions, FilterType, ServiceItem, StationBoard, MAX_ROWS, MAX_TIME_WINDOW,
+};
+pub use category::{ServiceType, TrainCategory, UnknownServiceType};
+pub use departures::{DepartureItem, DeparturesBoard, MAX_DESTINATIONS};
+pub use disruptions::{DisruptionCategory, DisruptionList, DisruptionMessage, DisruptionSeverity};
+pub use fault::{ApiError, Fault};
+pub use formation::{Coach, Formation, LoadingCategory, TrainEnd};
+pub use history::{Timeline, TimelineEntry};
+pub use identifiers::{Crs, Headcode, InvalidIdentifier, Rid, Rsid, Tiploc, Uid};
+pub use parsable::{ErrorLocation, Parsable, ParsingError, ParsingWarning};
+pub use query::{QueryOptions, ServiceList, ServiceSummary};
+pub use reasons::{Reason, ReasonCatalogue, ReasonCode, ReasonCodeList};
+pub use reference::{ReferenceData, Station, StationList, Toc, TocList};
+pub use request::{
+    escape, BoardOperation, BoardRequest, DeparturesOperation, DeparturesRequest,
+    DisruptionListRequest, HistoricServiceDetailsRequest, HistoricTimelineRequest,
+    QueryServicesRequest, ReasonCodeListRequest, ReasonCodeRequest, Service, ServiceDetailsRequest,
+    SoapRequest, SourceInstanceNamesRequest, StationListRequest, TocListRequest,
+    REFERENCE_NAMESPACE, SOAP_NAMESPACE, TOKEN_NAMESPACE,
+};
+pub use services::{
+    ForecastType, Lateness, LatenessTolerance, Location, ServiceDetails, ServiceLocation,
+    ServiceTime, UserLateness,
+};
+pub use sources::{SourceInstance, SourceInstanceList};
+pub use timezone::uk_time;
+pub use version::{ApiVersion, UnknownApiVersion, ENDPOINT_BASE};
+
+#[cfg(feature = "client")]
+pub use client::{FetchError, LdbsvClient, DEFAULT_ENDPOINT, DEFAULT_REFERENCE_ENDPOINT};
 #[cfg(feature = "pretty")]
-pub use prettyprint::PrettyPrintable;
+pub use prettyprint::{PrettyOptions, PrettyPrintable};
 
+mod activities;
 mod associations;
+mod boards;
+mod category;
+mod departures;
+mod disruptions;
+mod fault;
+mod formation;
+mod history;
+mod identifiers;
 mod parsable;
+mod query;
+mod reasons;
+mod reference;
+mod request;
 mod services;
+mod sources;
+mod timezone;
+mod version;
 
+#[cfg(feature = "client")]
+mod client;
 #[cfg(feature = "pretty")]
 mod prettyprint;
-
-// Why are these macros and not consts?
-// For some reason, format! does not support
-// consts.
-
-/*
-
-/// A fetch error.
-/// This describes an error that occurred while making a request to OpenLDBSVWS.
-#[derive(Error, Debug)]
-#[cfg(feature = "reqwest")]
-#[cfg(feature = "roxmltree")]
-pub enum FetchError<'a> {
-    /// An error returned by the server.
-    #[error("server responded with error {error:?}")]
-    StatusError { error: u16 },
-    /// An error while sending the request.
-    #[error("couldn't send request")]
-    RequestError { source: reqwest::Error },
-    /// An error while parsing the XML document into a struct.
-    #[error("couldn't parse")]
-    ParseError { source: ParsingError<'a> },
-    /// An error while parsing the response into an XML document.
-    #[error("malformed XML document")]
-    ParseXMLError { source: roxmltree::Error },
-}
-
-#[cfg(feature = "reqwest")]
-#[cfg(feature = "roxmltree")]
-pub async fn get_arrival_details<'a>(
-    client: Client,
-    token: &str,
-    station: &str,
-) -> Result<Document<'a>, FetchError<'a>> {
-    todo!()
-}
-
-#[cfg(feature = "reqwest")]
-#[cfg(feature = "roxmltree")]
-pub async fn get_departure_details<'a>(
-    client: Client,
-    token: &str,
-    station: &str,
-) -> Result<Document<'a>, FetchError<'a>> {
-    todo!()
-}
-
-/// Gets the service details of a service given it's RTTI ID and a valid OpenLDBSVWS (not OpenLDBWS)
-/// token.
-#[cfg(feature = "reqwest")]
-#[cfg(feature = "roxmltree")]
-pub async fn get_service_details<'a>(
-    client: Client,
-    token: &str,
-    rid: &str,
-) -> Result<ServiceDetails<'a>, FetchError<'a>> {
-
-}
-
-
- */
@@ -1,6 +1,129 @@
-use roxmltree::Node;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::fault::{ApiError, Fault};
+use crate::identifiers::Identifier;
+
+/// Where in a response an error happened.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// The path to the element or attribute, starting at the result, such as
+    /// `GetServiceDetailsResult/locations/location[12]/activities`. Repeated elements are numbered from 1, as in
+    /// XPath.
+    pub path: String,
+    /// The byte offset into the response.
+    pub position: usize,
+    /// The line of the response, starting at 1.
+    pub line: u32,
+    /// The column of the line, starting at 1.
+    pub column: u32,
+}
+
+impl ErrorLocation {
+    /// Finds the location of a node, and of `field` within it if given. Fields are looked up as child elements, then
+    /// attributes. A field which can't be found is still added to the path, but the position is that of the node.
+    fn new(node: &Node, field: Option<&str>) -> Self {
+        let mut path = Vec::new();
+
+        for ancestor in node.ancestors().filter(|x| x.is_element()) {
+            let name = ancestor.tag_name().name();
+            let siblings = || {
+                ancestor
+                    .parent()
+                    .into_iter()
+                    .flat_map(|parent| parent.children())
+                    .filter(|x| x.is_element() && x.tag_name().name() == name)
+            };
+
+            if siblings().nth(1).is_some() {
+                let index = ancestor
+                    .prev_siblings()
+                    .filter(|x| x.is_element() && x.tag_name().name() == name)
+                    .count();
+
+                path.push(format!("{}[{}]", name, index));
+            } else {
+                path.push(name.to_string());
+            }
+
+            // Everything above the result is the same SOAP envelope for every response.
+            if name.ends_with("Result") {
+                break;
+            }
+        }
+
+        path.reverse();
+
+        let mut position = node.range().start;
+
+        if let Some(field) = field {
+            if let Some(child) = node.children().find(|x| x.has_tag_name(field)) {
+                position = child.range().start;
+                path.push(field.to_string());
+            } else if let Some(attribute) = node.attribute_node(field) {
+                position = attribute.range().start;
+                path.push(format!("@{}", field));
+            } else {
+                path.push(field.to_string());
+            }
+        }
+
+        let text = node.document().text_pos_at(position);
+
+        ErrorLocation {
+            path: path.join("/"),
+            position,
+            line: text.row,
+            column: text.col,
+        }
+    }
+}
+
+impl Display for ErrorLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (line {}, column {})",
+            self.path, self.line, self.column
+        )
+    }
+}
+
+/// A non-fatal error found while parsing leniently. Whatever the error was in is left out of the result.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsingWarning {
+    /// Where the error happened, if known.
+    pub location: Option<ErrorLocation>,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl<'a> From<ParsingError<'a>> for ParsingWarning {
+    fn from(error: ParsingError<'a>) -> Self {
+        ParsingWarning {
+            location: error.location().cloned(),
+            message: error.kind().to_string(),
+        }
+    }
+}
+
+impl Display for ParsingWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} at {}", self.message, location),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 /// A parsing error.
 /// This describes an error that occurred while translating XML into a struct.
 #[derive(Error, Debug)]
@@ -10,13 +133,13 @@ pub enum ParsingError<'a> {
     InvalidTagName(&'static str),
     /// An invalid activity. The string represents the activity that was found.
     #[error("invalid activity, got {0}")]
-    InvalidActivity(&'a str),
+    InvalidActivity(Cow<'a, str>),
     /// An invalid forecast type. The string represents the forecast type that was found.
     #[error("invalid forecast type, got {0}")]
-    InvalidForecast(&'a str),
+    InvalidForecast(Cow<'a, str>),
     /// An invalid association category type. The string represents the category that was found.
     #[error("invalid association category, expected Join or Divide, got {0}")]
-    InvalidAssociationCategory(&'a str),
+    InvalidAssociationCategory(Cow<'a, str>),
     /// A missing field. The string represents the field that was not found.
     #[error("field {0} is missing")]
     MissingField(&'static str),
@@ -28,14 +151,185 @@ pub enum ParsingError<'a> {
         /// What was expected. This is purely for diagnostic reasons.
         expected: &'static str,
         /// The contents of the field.
-        found: Option<&'a str>,
+        found: Option<Cow<'a, str>>,
     },
     /// An unsupported service type. The string represents the service type that was found.
     #[error("unsupported service type {0}")]
-    UnsupportedServiceType(&'a str),
+    UnsupportedServiceType(Cow<'a, str>),
     /// XML parsing error.
     #[error("cannot parse XML")]
     XMLParseError { source: roxmltree::Error },
+    /// The response is a SOAP fault rather than a result.
+    #[error(transparent)]
+    Api(ApiError),
+    /// An error at a known location in the response.
+    #[error("{error} at {location}")]
+    At {
+        /// Where the error happened.
+        location: ErrorLocation,
+        /// The error.
+        error: Box<ParsingError<'a>>,
+    },
+}
+
+impl<'a> ParsingError<'a> {
+    /// Copies any borrowed strings, so that this error no longer borrows from the response.
+    pub fn into_owned(self) -> ParsingError<'static> {
+        match self {
+            ParsingError::InvalidTagName(name) => ParsingError::InvalidTagName(name),
+            ParsingError::InvalidActivity(x) => ParsingError::InvalidActivity(owned(x)),
+            ParsingError::InvalidForecast(x) => ParsingError::InvalidForecast(owned(x)),
+            ParsingError::InvalidAssociationCategory(x) => {
+                ParsingError::InvalidAssociationCategory(owned(x))
+            }
+            ParsingError::MissingField(field) => ParsingError::MissingField(field),
+            ParsingError::InvalidField {
+                field,
+                expected,
+                found,
+            } => ParsingError::InvalidField {
+                field,
+                expected,
+                found: found.map(owned),
+            },
+            ParsingError::UnsupportedServiceType(x) => {
+                ParsingError::UnsupportedServiceType(owned(x))
+            }
+            ParsingError::XMLParseError { source } => ParsingError::XMLParseError { source },
+            ParsingError::Api(error) => ParsingError::Api(error),
+            ParsingError::At { location, error } => ParsingError::At {
+                location,
+                error: Box::new(error.into_owned()),
+            },
+        }
+    }
+
+    /// Where the error happened, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match self {
+            ParsingError::At { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The error itself, without its location.
+    pub fn kind(&self) -> &ParsingError<'a> {
+        match self {
+            ParsingError::At { error, .. } => error.kind(),
+            error => error,
+        }
+    }
+
+    /// The name of the field this error is about, if any.
+    fn field(&self) -> Option<&'static str> {
+        match self {
+            ParsingError::MissingField(field) | ParsingError::InvalidField { field, .. } => {
+                Some(field)
+            }
+            ParsingError::InvalidActivity(_) => Some("activities"),
+            ParsingError::InvalidAssociationCategory(_) => Some("category"),
+            ParsingError::UnsupportedServiceType(_) => Some("serviceType"),
+            _ => None,
+        }
+    }
+
+    /// Records that this error happened within `node`. Errors which already have a location keep it, as it is more
+    /// specific, and errors in the XML itself carry their own position.
+    pub(crate) fn at(self, node: &Node) -> Self {
+        match self {
+            ParsingError::At { .. } | ParsingError::XMLParseError { .. } | ParsingError::Api(_) => {
+                self
+            }
+            error => ParsingError::At {
+                location: ErrorLocation::new(node, error.field()),
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+/// The error for a response without the expected result. Failed requests return a SOAP fault instead of a result, so
+/// the fault is returned if there is one.
+pub(crate) fn missing_result<'a>(document: &Document, result: &'static str) -> ParsingError<'a> {
+    match Fault::find(document) {
+        Some(fault) => ParsingError::Api(fault.into()),
+        None => ParsingError::MissingField(result),
+    }
+}
+
+/// How to deal with errors which don't have to spoil the whole result, such as an unknown activity code.
+pub(crate) enum Mode<'w> {
+    /// Fail on any error.
+    Strict,
+    /// Collect non-fatal errors as warnings and carry on without whatever they were in.
+    Lenient(&'w mut Vec<ParsingWarning>),
+}
+
+impl<'w> Mode<'w> {
+    /// Handles the result of parsing part of `node`. Errors are given the location of `node`, and then are either
+    /// returned or, in lenient mode, collected as warnings, leaving None.
+    pub(crate) fn recover<'c, T>(
+        &mut self,
+        node: &Node,
+        result: Result<T, ParsingError<'c>>,
+    ) -> Result<Option<T>, ParsingError<'c>> {
+        match (result, self) {
+            (Ok(x), _) => Ok(Some(x)),
+            (Err(e), Mode::Strict) => Err(e.at(node)),
+            (Err(e), Mode::Lenient(warnings)) => {
+                warnings.push(e.at(node).into());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Checks an identifier which can't be left out, such as a RID, found in `field` of `node`. In lenient mode, an
+    /// invalid identifier is kept as it is, with a warning, as it is still what the API knows the service by.
+    pub(crate) fn identifier<'c, T: Identifier<'c>>(
+        &mut self,
+        node: &Node,
+        field: &'static str,
+        value: &'c str,
+    ) -> Result<T, ParsingError<'c>> {
+        let error = match T::checked(value) {
+            Ok(identifier) => return Ok(identifier),
+            Err(_) => ParsingError::InvalidField {
+                field,
+                expected: T::EXPECTED,
+                found: Some(value.into()),
+            },
+        };
+
+        match self {
+            Mode::Strict => Err(error.at(node)),
+            Mode::Lenient(warnings) => {
+                warnings.push(error.at(node).into());
+                Ok(T::unchecked(value))
+            }
+        }
+    }
+
+    /// Checks an optional identifier, such as a CRS code, found in `field` of `node`. A missing identifier is None,
+    /// and an invalid one is an error, or in lenient mode a warning, leaving None.
+    pub(crate) fn optional_identifier<'c, T: Identifier<'c>>(
+        &mut self,
+        node: &Node,
+        field: &'static str,
+        value: Option<&'c str>,
+    ) -> Result<Option<T>, ParsingError<'c>> {
+        match value {
+            Some(value) => {
+                let identifier = T::checked(value).map_err(|_| ParsingError::InvalidField {
+                    field,
+                    expected: T::EXPECTED,
+                    found: Some(value.into()),
+                });
+
+                Ok(self.recover(node, identifier)?)
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 #[macro_export]
@@ -68,11 +362,8 @@ macro_rules! text {
                         return "";
                     }
 
-                    // Then get the range:
-                    let range = text.range();
-
-                    // Then map that range to the original string:
-                    &$t[range.start..range.end]
+                    // Then map its range to the original string, which may not be the one the node is from:
+                    $t.get(text.range()).unwrap_or("")
                 } else {
                     ""
                 }
@@ -80,6 +371,63 @@ macro_rules! text {
     };
 }
 
+/// Gets the text of a child element as an identifier, such as a `Rid`, checked with `Mode::identifier`. Fails if
+/// the element is missing.
+#[macro_export]
+macro_rules! identifier {
+    ($mode: expr, $t: expr, $x: expr, $y: literal) => {
+        text!($t, $x, $y).and_then(|x| $mode.identifier(&$x, $y, x))
+    };
+}
+
+/// Like `identifier!`, but for an optional identifier, checked with `Mode::optional_identifier`.
+#[macro_export]
+macro_rules! optional_identifier {
+    ($mode: expr, $t: expr, $x: expr, $y: literal) => {
+        $mode.optional_identifier(&$x, $y, text!($t, $x, $y).ok())
+    };
+}
+
+/// Like `text!`, but with entities such as `&amp;` decoded. Use this rather than `text!` for free text, such as names.
+/// The text is borrowed from `$t` if it has no entities, and copied if it does.
+#[macro_export]
+macro_rules! decoded_text {
+    ($t: expr, $x: expr, $y: literal) => {
+        $x.children()
+            .find(|x| x.has_tag_name($y))
+            .ok_or(ParsingError::MissingField($y))
+            .map(|x| match x.first_child().filter(|x| x.is_text()) {
+                Some(text) => $crate::parsable::decoded(
+                    $t.get(text.range()).unwrap_or(""),
+                    text.text().unwrap_or(""),
+                ),
+                None => std::borrow::Cow::Borrowed(""),
+            })
+    };
+}
+
+#[macro_export]
+macro_rules! attr {
+    ($t: expr, $x: expr, $y: literal) => {
+        $x.attribute_node($y)
+            .ok_or(ParsingError::MissingField($y))
+            .map(|x| {
+                // Same story as text!: map the value's range to the original string.
+                $t.get(x.value_range()).unwrap_or("")
+            })
+    };
+}
+
+/// Like `attr!`, but with entities decoded, as `decoded_text!` is to `text!`.
+#[macro_export]
+macro_rules! decoded_attr {
+    ($t: expr, $x: expr, $y: literal) => {
+        $x.attribute_node($y)
+            .ok_or(ParsingError::MissingField($y))
+            .map(|x| $crate::parsable::decoded($t.get(x.value_range()).unwrap_or(""), x.value()))
+    };
+}
+
 #[macro_export]
 macro_rules! time {
     ($t: expr, $x: expr, $y: literal) => {
@@ -88,7 +436,7 @@ macro_rules! time {
                 DateTime::parse_from_rfc3339(text).map_err(|_| ParsingError::InvalidField {
                     field: $y,
                     expected: "DateTime",
-                    found: Some(text),
+                    found: Some(text.into()),
                 })
             }
             Err(e) => Err(e),
@@ -104,7 +452,7 @@ macro_rules! date {
         NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| ParsingError::InvalidField {
             field: $y,
             expected: "NaiveDate",
-            found: Some(text),
+            found: Some(text.into()),
         })
     }};
 }
@@ -120,7 +468,7 @@ macro_rules! bool {
                 x => Err(ParsingError::InvalidField {
                     field: $y,
                     expected: "bool",
-                    found: Some(x),
+                    found: Some(x.into()),
                 }),
             },
             Err(_) => Ok($z),
@@ -135,6 +483,35 @@ macro_rules! parse {
     };
 }
 
+/// Something which can be parsed from an element of a response. Fields borrow from `string`, which should be the
+/// response the node was parsed from. If it isn't, parsing fails or gives nonsense, but never panics.
 pub trait Parsable<'a, 'b, 'c>: Sized {
     fn parse(from: &Node<'a, 'b>, string: &'c str) -> Result<Self, ParsingError<'c>>;
+
+    /// Like `parse`, but errors record where in the response they happened.
+    fn parse_located(from: &Node<'a, 'b>, string: &'c str) -> Result<Self, ParsingError<'c>> {
+        Self::parse(from, string).map_err(|e| e.at(from))
+    }
+}
+
+/// Maps the range of a node back to the original string. Parsers are public, so `string` may not be the string the node
+/// was parsed from, in which case the range may be out of bounds or split a character. That gives an empty string
+/// rather than a panic, so parsing fails with an error about the field instead.
+pub(crate) fn slice(string: &str, range: Range<usize>) -> &str {
+    string.get(range).unwrap_or("")
+}
+
+/// Copies a borrowed string if needed, so that it no longer borrows from the response. Used by `into_owned`.
+pub(crate) fn owned(string: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(string.into_owned())
+}
+
+/// Picks between the raw text of a value in the response and the value decoded by roxmltree. They only differ if the
+/// value has entities, such as `&amp;`, or `raw` isn't from the right string, so the value can usually be borrowed.
+pub(crate) fn decoded<'a>(raw: &'a str, value: &str) -> Cow<'a, str> {
+    if raw == value {
+        Cow::Borrowed(raw)
+    } else {
+        Cow::Owned(value.to_string())
+    }
 }
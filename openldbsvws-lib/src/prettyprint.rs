@@ -1,3 +1,68 @@
+use chrono::{DateTime, FixedOffset, Timelike};
+
+use crate::reference::ReferenceData;
+
+/// Options for pretty printing.
+#[derive(Debug, Clone, Default)]
+pub struct PrettyOptions<'a> {
+    /// If true, show working times to the half minute, rather than public times.
+    pub working: bool,
+    /// Reference data to name stations with, where the response only gives their CRS codes.
+    pub reference: Option<&'a ReferenceData>,
+}
+
+impl<'a> PrettyOptions<'a> {
+    /// Names a station given its CRS code, such as "York (YRK)", or just gives the code if the station isn't known.
+    pub(crate) fn station(&self, crs: &str) -> String {
+        match self
+            .reference
+            .and_then(|reference| reference.station_name(crs))
+        {
+            Some(name) => format!("{} ({})", name, crs),
+            None => crs.to_string(),
+        }
+    }
+}
+
 pub trait PrettyPrintable {
     fn pretty(&self) -> String;
+
+    /// Like `pretty`, but with options. Types which have no use for the options ignore them.
+    fn pretty_with(&self, _options: &PrettyOptions) -> String {
+        self.pretty()
+    }
+}
+
+/// Formats a scheduled time. Working times are given to the half minute, which is marked with ½.
+pub(crate) fn format_scheduled(time: Option<DateTime<FixedOffset>>, working: bool) -> String {
+    match (time, working) {
+        (Some(time), true) if time.second() >= 30 => format!("{}½", time.format("%H:%M")),
+        (Some(time), true) => format!("{} ", time.format("%H:%M")),
+        (Some(time), false) => time.format("%H:%M").to_string(),
+        (None, true) => "--:-- ".to_string(),
+        (None, false) => "--:--".to_string(),
+    }
 }
+
+pub(crate) const GREY: u8 = 247;
+pub(crate) const PURPLE: u8 = 140;
+pub(crate) const PASSED: u8 = 81;
+pub(crate) const LIGHT_PASSED: u8 = 195;
+pub(crate) const HERE: u8 = 155;
+pub(crate) const LIGHT_HERE: u8 = 193;
+pub(crate) const LATE: u8 = 220;
+pub(crate) const LIGHT_LATE: u8 = 230;
+pub(crate) const CANCELLED: u8 = 203;
+pub(crate) const LIGHT_CANCELLED: u8 = 218;
+pub(crate) const SCHEDULED: u8 = 183;
+pub(crate) const LIGHT_SCHEDULED: u8 = 225;
+
+pub(crate) const INDENT: &str = "    ";
+pub(crate) const CIRCLE: &str = "●";
+pub(crate) const LINE: &str = "│";
+pub(crate) const ARROW: &str = "⟶";
+pub(crate) const ARROW_LEFT: &str = "⟵";
+pub(crate) const CROSS: &str = "⨯";
+pub(crate) const DOTTED_CIRCLE: &str = "◯";
+pub(crate) const SEMI_CIRCLE_1: &str = "◔";
+pub(crate) const SEMI_CIRCLE_3: &str = "◕";
@@ -0,0 +1,217 @@
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::identifiers::{Headcode, Rid, Rsid, Uid};
+use crate::parsable::{missing_result, Mode, Parsable, ParsingError, ParsingWarning};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyPrintable, GREY, INDENT, LIGHT_SCHEDULED, PURPLE};
+use crate::services::Location;
+use crate::{date, decoded_text, identifier, name, optional_identifier, text, time};
+
+/// Options for `QueryServices` requests.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions<'a> {
+    /// Only return services running around this time.
+    pub filter_time: Option<NaiveTime>,
+    /// Only return services which call at this station.
+    pub filter_crs: Option<&'a str>,
+    /// Only return services operated by this operator.
+    pub filter_toc: Option<&'a str>,
+}
+
+/// A summary of a service, as returned by `QueryServices`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ServiceSummary<'a> {
+    /// A unique RTTI ID for this service that can be used to obtain full details of the service.
+    pub rid: Rid<'a>,
+    /// The TSDB Train UID value for this service, or if one is not available, then an RTTI allocated replacement.
+    pub uid: Uid<'a>,
+    /// The Train ID value (headcode) for this service.
+    pub trainid: Headcode<'a>,
+    /// The Retail Service ID for this service, if known.
+    pub rsid: Option<Rsid<'a>>,
+    /// The Scheduled Departure Date of this service.
+    pub sdd: NaiveDate,
+    /// The origin location of this service.
+    pub origin: Option<Location<'a>>,
+    /// The destination location of this service.
+    pub destination: Option<Location<'a>>,
+    /// The scheduled departure time of this service from its origin.
+    pub scheduled_departure: Option<DateTime<FixedOffset>>,
+    /// The scheduled arrival time of this service at its destination.
+    pub scheduled_arrival: Option<DateTime<FixedOffset>>,
+}
+
+impl<'a> ServiceSummary<'a> {
+    /// Copies any borrowed strings, so that this summary no longer borrows from the response.
+    pub fn into_owned(self) -> ServiceSummary<'static> {
+        ServiceSummary {
+            rid: self.rid.into_owned(),
+            uid: self.uid.into_owned(),
+            trainid: self.trainid.into_owned(),
+            rsid: self.rsid.map(Rsid::into_owned),
+            sdd: self.sdd,
+            origin: self.origin.map(Location::into_owned),
+            destination: self.destination.map(Location::into_owned),
+            scheduled_departure: self.scheduled_departure,
+            scheduled_arrival: self.scheduled_arrival,
+        }
+    }
+}
+
+impl<'b> ServiceSummary<'b> {
+    /// Parses a summary. In lenient mode, invalid identifiers are kept or left out with a warning, as with
+    /// `ServiceDetails::parse_lenient`.
+    pub(crate) fn parse_with<'a>(
+        service: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<Self, ParsingError<'b>> {
+        if name!(service) != "service" {
+            return Err(ParsingError::InvalidTagName("service"));
+        }
+
+        Ok(ServiceSummary {
+            rid: identifier!(mode, string, service, "rid")?,
+            uid: identifier!(mode, string, service, "uid")?,
+            trainid: identifier!(mode, string, service, "trainid")?,
+            rsid: optional_identifier!(mode, string, service, "rsid")?,
+            sdd: date!(string, service, "sdd")?,
+            origin: match decoded_text!(string, service, "origin") {
+                Ok(name) => Some(Location {
+                    name,
+                    crs: optional_identifier!(mode, string, service, "originCRS")?,
+                    tiploc: optional_identifier!(mode, string, service, "originTiploc")?,
+                }),
+                Err(_) => None,
+            },
+            destination: match decoded_text!(string, service, "destination") {
+                Ok(name) => Some(Location {
+                    name,
+                    crs: optional_identifier!(mode, string, service, "destCRS")?,
+                    tiploc: optional_identifier!(mode, string, service, "destTiploc")?,
+                }),
+                Err(_) => None,
+            },
+            scheduled_departure: time!(string, service, "std").ok(),
+            scheduled_arrival: time!(string, service, "sta").ok(),
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceSummary<'b> {
+    fn parse(service: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        ServiceSummary::parse_with(service, string, &mut Mode::Strict)
+    }
+}
+
+/// The services matching a `QueryServices` request.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct ServiceList<'a> {
+    /// The matching services.
+    pub services: Vec<ServiceSummary<'a>>,
+    /// Problems found while parsing leniently with `parse_lenient`. Always empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<ParsingWarning>,
+}
+
+impl<'a> ServiceList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> ServiceList<'static> {
+        ServiceList {
+            services: self
+                .services
+                .into_iter()
+                .map(ServiceSummary::into_owned)
+                .collect(),
+            warnings: self.warnings,
+        }
+    }
+
+    /// Parses a list of services leniently. Unlike `try_from`, a service which can't be parsed is left out with a
+    /// warning in `warnings`, and invalid identifiers are kept as they are, or left out if optional, with a warning.
+    pub fn parse_lenient(string: &'a str) -> Result<ServiceList<'a>, ParsingError<'a>> {
+        let mut warnings = Vec::new();
+        let mut list = ServiceList::parse_with(string, &mut Mode::Lenient(&mut warnings))?;
+
+        list.warnings = warnings;
+
+        Ok(list)
+    }
+
+    /// Finds and parses the result of a response.
+    fn parse_with(string: &'a str, mode: &mut Mode) -> Result<ServiceList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("QueryServicesResult"))
+            .ok_or_else(|| missing_result(&document, "QueryServicesResult"))?;
+
+        let mut services = Vec::new();
+
+        // The list is missing entirely if nothing matched.
+        if let Some(list) = result.children().find(|x| x.has_tag_name("services")) {
+            for node in list.children().filter(|x| x.is_element()) {
+                let service = ServiceSummary::parse_with(&node, string, mode);
+
+                if let Some(service) = mode.recover(&node, service)? {
+                    services.push(service)
+                }
+            }
+        }
+
+        Ok(ServiceList {
+            services,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ServiceList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<ServiceList<'a>, ParsingError<'a>> {
+        ServiceList::parse_with(string, &mut Mode::Strict)
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for ServiceSummary<'a> {
+    fn pretty(&self) -> String {
+        fn location<'b>(location: &'b Option<Location>) -> &'b str {
+            match location {
+                Some(location) => &location.name,
+                None => "unknown",
+            }
+        }
+
+        let strings: &[ANSIString] = &[
+            Style::default().paint(INDENT),
+            Fixed(LIGHT_SCHEDULED).paint(match self.scheduled_departure {
+                Some(time) => time.format("%H:%M").to_string(),
+                None => "--:--".to_string(),
+            }),
+            Style::default().paint(" "),
+            Fixed(PURPLE).bold().paint(&*self.trainid),
+            Style::default().paint(" "),
+            Style::default().paint(location(&self.origin)),
+            Fixed(GREY).paint(" to "),
+            Style::default().bold().paint(location(&self.destination)),
+            Fixed(GREY).paint(format!(" ({}, {})\n", self.rid, self.uid)),
+        ];
+
+        ANSIStrings(strings).to_string()
+    }
+}
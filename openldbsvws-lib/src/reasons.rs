@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::parsable::{missing_result, owned, slice, Parsable, ParsingError};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::PrettyPrintable;
+use crate::{attr, decoded_text, name, parse, text};
+
+/// The reason a service is delayed or cancelled, as given by the service. Only the reason code is given, so its text
+/// has to be looked up in a `ReasonCatalogue`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Reason<'a> {
+    /// The reason code.
+    pub code: u16,
+    /// The TIPLOC of the location the reason applies to, if any.
+    pub tiploc: Option<Cow<'a, str>>,
+    /// If true, the reason applies near `tiploc` rather than at it.
+    pub near: bool,
+    /// The human readable text of the reason, once resolved with `ReasonCatalogue`.
+    pub text: Option<String>,
+}
+
+impl<'a> Reason<'a> {
+    /// Copies any borrowed strings, so that this reason no longer borrows from the response.
+    pub fn into_owned(self) -> Reason<'static> {
+        Reason {
+            code: self.code,
+            tiploc: self.tiploc.map(owned),
+            near: self.near,
+            text: self.text,
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Reason<'b> {
+    fn parse(reason: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(reason) != "cancelReason" && name!(reason) != "delayReason" {
+            return Err(ParsingError::InvalidTagName("cancelReason or delayReason"));
+        }
+
+        // Unlike most fields, the code is the text of the reason itself.
+        let code = match reason.first_child().filter(|x| x.is_text()) {
+            Some(text) => slice(string, text.range()),
+            None => "",
+        };
+
+        Ok(Reason {
+            code: code.parse().map_err(|_| ParsingError::InvalidField {
+                field: "reason",
+                expected: "u16",
+                found: Some(code.into()),
+            })?,
+            tiploc: attr!(string, reason, "tiploc").ok().map(Cow::from),
+            near: match attr!(string, reason, "near") {
+                Ok("true") => true,
+                Ok("false") | Err(_) => false,
+                Ok(x) => {
+                    return Err(ParsingError::InvalidField {
+                        field: "near",
+                        expected: "bool",
+                        found: Some(x.into()),
+                    })
+                }
+            },
+            text: None,
+        })
+    }
+}
+
+/// A reason code, with its text for late running and cancellations.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ReasonCode<'a> {
+    /// The reason code.
+    pub code: u16,
+    /// The text used when a service is delayed for this reason.
+    pub late_reason: Cow<'a, str>,
+    /// The text used when a service is cancelled for this reason.
+    pub cancellation_reason: Cow<'a, str>,
+}
+
+impl<'a> ReasonCode<'a> {
+    /// Copies any borrowed strings, so that this reason code no longer borrows from the response.
+    pub fn into_owned(self) -> ReasonCode<'static> {
+        ReasonCode {
+            code: self.code,
+            late_reason: owned(self.late_reason),
+            cancellation_reason: owned(self.cancellation_reason),
+        }
+    }
+
+    /// Parses the fields of a reason code. `GetReasonCode` puts these straight in its result, whereas
+    /// `GetReasonCodeList` wraps each in a `reason` element.
+    fn from_node<'b>(reason: &Node<'b, 'b>, string: &'a str) -> Result<Self, ParsingError<'a>> {
+        Ok(ReasonCode {
+            code: parse!(string, reason, "code", u16).map_err(|_| ParsingError::InvalidField {
+                field: "code",
+                expected: "u16",
+                found: text!(string, reason, "code").ok().map(Cow::from),
+            })?,
+            late_reason: decoded_text!(string, reason, "lateReason")?,
+            cancellation_reason: decoded_text!(string, reason, "cancReason")?,
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for ReasonCode<'b> {
+    fn parse(reason: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(reason) != "reason" {
+            return Err(ParsingError::InvalidTagName("reason"));
+        }
+
+        ReasonCode::from_node(reason, string)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ReasonCode<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<ReasonCode<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetReasonCodeResult"))
+            .ok_or_else(|| missing_result(&document, "GetReasonCodeResult"))?;
+
+        ReasonCode::from_node(&result, string)
+    }
+}
+
+/// Every reason code, as returned by `GetReasonCodeList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct ReasonCodeList<'a> {
+    /// The reason codes.
+    pub reasons: Vec<ReasonCode<'a>>,
+}
+
+impl<'a> ReasonCodeList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> ReasonCodeList<'static> {
+        ReasonCodeList {
+            reasons: self
+                .reasons
+                .into_iter()
+                .map(ReasonCode::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ReasonCodeList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<ReasonCodeList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetReasonCodeListResult"))
+            .ok_or_else(|| missing_result(&document, "GetReasonCodeListResult"))?;
+
+        let mut reasons = Vec::new();
+
+        for node in result.children().filter(|x| x.is_element()) {
+            reasons.push(ReasonCode::parse_located(&node, string)?)
+        }
+
+        Ok(ReasonCodeList { reasons })
+    }
+}
+
+/// A catalogue of reason codes, used to resolve the reasons given by services into text.
+///
+/// The reason codes rarely change, so the catalogue owns its text and can be loaded once with `GetReasonCodeList` and
+/// kept for as long as needed.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ReasonCatalogue {
+    reasons: BTreeMap<u16, (String, String)>,
+}
+
+impl ReasonCatalogue {
+    /// Creates an empty catalogue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a reason code to the catalogue, replacing any existing text for the code.
+    pub fn insert(&mut self, reason: &ReasonCode) {
+        self.reasons.insert(
+            reason.code,
+            (
+                reason.late_reason.to_string(),
+                reason.cancellation_reason.to_string(),
+            ),
+        );
+    }
+
+    /// The number of reason codes in the catalogue.
+    pub fn len(&self) -> usize {
+        self.reasons.len()
+    }
+
+    /// If true, the catalogue has no reason codes.
+    pub fn is_empty(&self) -> bool {
+        self.reasons.is_empty()
+    }
+
+    /// The text used when a service is delayed for the reason `code`.
+    pub fn late_reason(&self, code: u16) -> Option<&str> {
+        self.reasons.get(&code).map(|(late, _)| late.as_str())
+    }
+
+    /// The text used when a service is cancelled for the reason `code`.
+    pub fn cancellation_reason(&self, code: u16) -> Option<&str> {
+        self.reasons
+            .get(&code)
+            .map(|(_, cancellation)| cancellation.as_str())
+    }
+
+    /// Appends the location of a reason to its text. `location` is the name of the location, if it is known,
+    /// otherwise the TIPLOC is used.
+    fn with_location(text: &str, reason: &Reason, location: Option<&str>) -> String {
+        match location.or(reason.tiploc.as_deref()) {
+            Some(location) if reason.near => format!("{} near {}", text, location),
+            Some(location) => format!("{} at {}", text, location),
+            None => text.to_string(),
+        }
+    }
+
+    /// Resolves the reason a service is delayed into text, such as "This train has been delayed by a fault on the
+    /// signalling system near Stevenage". `location` is the name of the reason's location, if known.
+    pub fn resolve_late(&self, reason: &Reason, location: Option<&str>) -> Option<String> {
+        self.late_reason(reason.code)
+            .map(|text| Self::with_location(text, reason, location))
+    }
+
+    /// Resolves the reason a service is cancelled into text, such as "This train has been cancelled because of a
+    /// fault on the signalling system near Stevenage". `location` is the name of the reason's location, if known.
+    pub fn resolve_cancellation(&self, reason: &Reason, location: Option<&str>) -> Option<String> {
+        self.cancellation_reason(reason.code)
+            .map(|text| Self::with_location(text, reason, location))
+    }
+}
+
+impl<'a> From<&ReasonCodeList<'a>> for ReasonCatalogue {
+    fn from(list: &ReasonCodeList<'a>) -> Self {
+        let mut catalogue = ReasonCatalogue::new();
+
+        for reason in &list.reasons {
+            catalogue.insert(reason);
+        }
+
+        catalogue
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for Reason<'a> {
+    fn pretty(&self) -> String {
+        match &self.text {
+            Some(text) => text.clone(),
+            None => ReasonCatalogue::with_location(&format!("reason {}", self.code), self, None),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::parsable::{missing_result, owned, Parsable, ParsingError};
+use crate::{attr, decoded_attr, name};
+
+/// A train operating company, as returned by `GetTOCList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Toc<'a> {
+    /// The two letter code of this operator, such as "GW".
+    pub code: Cow<'a, str>,
+    /// The name of this operator.
+    pub name: Cow<'a, str>,
+}
+
+impl<'a> Toc<'a> {
+    /// Copies any borrowed strings, so that this operator no longer borrows from the response.
+    pub fn into_owned(self) -> Toc<'static> {
+        Toc {
+            code: owned(self.code),
+            name: owned(self.name),
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Toc<'b> {
+    fn parse(toc: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(toc) != "TOC" {
+            return Err(ParsingError::InvalidTagName("TOC"));
+        }
+
+        Ok(Toc {
+            code: decoded_attr!(string, toc, "toc")?,
+            name: decoded_attr!(string, toc, "Value")?,
+        })
+    }
+}
+
+/// A station, as returned by `GetStationList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Station<'a> {
+    /// The CRS code of this station.
+    pub crs: Cow<'a, str>,
+    /// The name of this station.
+    pub name: Cow<'a, str>,
+}
+
+impl<'a> Station<'a> {
+    /// Copies any borrowed strings, so that this station no longer borrows from the response.
+    pub fn into_owned(self) -> Station<'static> {
+        Station {
+            crs: owned(self.crs),
+            name: owned(self.name),
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Station<'b> {
+    fn parse(station: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(station) != "Station" {
+            return Err(ParsingError::InvalidTagName("Station"));
+        }
+
+        Ok(Station {
+            crs: decoded_attr!(string, station, "crs")?,
+            name: decoded_attr!(string, station, "Value")?,
+        })
+    }
+}
+
+/// Finds the result of a reference data request, returning it along with its version.
+fn result<'a, 'input>(
+    document: &'a Document<'input>,
+    string: &'input str,
+    tag: &'static str,
+) -> Result<(Node<'a, 'input>, &'input str), ParsingError<'input>> {
+    let result = document
+        .root()
+        .descendants()
+        .find(|x| x.tag_name().name() == tag)
+        .ok_or_else(|| missing_result(document, tag))?;
+
+    // The version is on the list itself, which is the only child of the result.
+    let list = result
+        .children()
+        .find(|x| x.is_element())
+        .ok_or(ParsingError::MissingField(tag))?;
+
+    Ok((list, attr!(string, list, "version").unwrap_or("")))
+}
+
+/// Every train operating company, as returned by `GetTOCList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct TocList<'a> {
+    /// The version of the list. Passing this to `GetTOCList` only returns operators if the list has changed.
+    pub version: Cow<'a, str>,
+    /// The operators.
+    pub tocs: Vec<Toc<'a>>,
+}
+
+impl<'a> TocList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> TocList<'static> {
+        TocList {
+            version: owned(self.version),
+            tocs: self.tocs.into_iter().map(Toc::into_owned).collect(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TocList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<TocList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let (list, version) = result(&document, string, "GetTOCListResult")?;
+
+        let mut tocs = Vec::new();
+
+        for node in list.children().filter(|x| x.is_element()) {
+            tocs.push(Toc::parse_located(&node, string)?)
+        }
+
+        Ok(TocList {
+            version: version.into(),
+            tocs,
+        })
+    }
+}
+
+/// Every station, as returned by `GetStationList`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct StationList<'a> {
+    /// The version of the list. Passing this to `GetStationList` only returns stations if the list has changed.
+    pub version: Cow<'a, str>,
+    /// The stations.
+    pub stations: Vec<Station<'a>>,
+}
+
+impl<'a> StationList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> StationList<'static> {
+        StationList {
+            version: owned(self.version),
+            stations: self.stations.into_iter().map(Station::into_owned).collect(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for StationList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<StationList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let (list, version) = result(&document, string, "GetStationListResult")?;
+
+        let mut stations = Vec::new();
+
+        for node in list.children().filter(|x| x.is_element()) {
+            stations.push(Station::parse_located(&node, string)?)
+        }
+
+        Ok(StationList {
+            version: version.into(),
+            stations,
+        })
+    }
+}
+
+/// A store of reference data, used to validate codes and look up names without making a request.
+///
+/// The store owns its data, so it can be kept for as long as needed. With the `serde` feature, it can be serialized to
+/// disk and reloaded offline.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceData {
+    toc_version: Option<String>,
+    tocs: BTreeMap<String, String>,
+    station_version: Option<String>,
+    stations: BTreeMap<String, String>,
+}
+
+impl ReferenceData {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the operators in the store with `list`. If the list is empty and has the same version as the store,
+    /// the operators are unchanged, so the store is left as it is.
+    pub fn update_tocs(&mut self, list: &TocList) {
+        if list.tocs.is_empty() && self.toc_version.as_deref() == Some(&*list.version) {
+            return;
+        }
+
+        self.toc_version = Some(list.version.to_string());
+        self.tocs = list
+            .tocs
+            .iter()
+            .map(|toc| (toc.code.to_string(), toc.name.to_string()))
+            .collect();
+    }
+
+    /// Replaces the stations in the store with `list`. If the list is empty and has the same version as the store,
+    /// the stations are unchanged, so the store is left as it is.
+    pub fn update_stations(&mut self, list: &StationList) {
+        if list.stations.is_empty() && self.station_version.as_deref() == Some(&*list.version) {
+            return;
+        }
+
+        self.station_version = Some(list.version.to_string());
+        self.stations = list
+            .stations
+            .iter()
+            .map(|station| (station.crs.to_string(), station.name.to_string()))
+            .collect();
+    }
+
+    /// The version of the operators in the store, to pass to `GetTOCList`.
+    pub fn toc_version(&self) -> Option<&str> {
+        self.toc_version.as_deref()
+    }
+
+    /// The version of the stations in the store, to pass to `GetStationList`.
+    pub fn station_version(&self) -> Option<&str> {
+        self.station_version.as_deref()
+    }
+
+    /// Looks up the name of an operator given its code.
+    pub fn toc_name(&self, code: &str) -> Option<&str> {
+        self.tocs
+            .get(&code.to_ascii_uppercase())
+            .map(|name| name.as_str())
+    }
+
+    /// Looks up the name of a station given its CRS code.
+    pub fn station_name(&self, crs: &str) -> Option<&str> {
+        self.stations
+            .get(&crs.to_ascii_uppercase())
+            .map(|name| name.as_str())
+    }
+
+    /// If true, `crs` is the CRS code of a known station.
+    pub fn is_valid_crs(&self, crs: &str) -> bool {
+        self.station_name(crs).is_some()
+    }
+
+    /// Iterates over the operators in the store as pairs of code and name, ordered by code.
+    pub fn tocs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tocs
+            .iter()
+            .map(|(code, name)| (code.as_str(), name.as_str()))
+    }
+
+    /// Iterates over the stations in the store as pairs of CRS code and name, ordered by CRS code.
+    pub fn stations(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.stations
+            .iter()
+            .map(|(crs, name)| (crs.as_str(), name.as_str()))
+    }
+}
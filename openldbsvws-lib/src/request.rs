@@ -0,0 +1,687 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+
+use crate::boards::{BoardOptions, FilterType, MAX_ROWS, MAX_TIME_WINDOW};
+use crate::departures::MAX_DESTINATIONS;
+use crate::query::QueryOptions;
+use crate::timezone::uk_time;
+use crate::version::ApiVersion;
+
+/// The namespace of the SOAP envelope.
+pub const SOAP_NAMESPACE: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+
+/// The namespace of the access token header.
+pub const TOKEN_NAMESPACE: &str = "http://thalesgroup.com/RTTI/2013-11-28/Token/types";
+
+/// The namespace of requests to the reference data endpoint. The reference data schema hasn't changed since 2015, so
+/// this is the same for every `ApiVersion`.
+pub const REFERENCE_NAMESPACE: &str = "http://thalesgroup.com/RTTI/2015-05-14/ldbsv_ref/";
+
+/// The prefix of the `SOAPAction` of requests to the main endpoint. Actions keep the namespace of the first version of
+/// the API, whatever version the request is.
+const LDBSV_ACTION: &str = "http://thalesgroup.com/RTTI/2012-01-13/ldbsv/";
+
+/// The prefix of the `SOAPAction` of requests to the reference data endpoint.
+const REFERENCE_ACTION: &str = "http://thalesgroup.com/RTTI/2015-05-14/ldbsv_ref/";
+
+/// The web services of OpenLDBSVWS. Each has its own endpoint and namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    /// The main service, for services and boards.
+    Ldbsv,
+    /// The reference data service, for operators and stations.
+    Reference,
+}
+
+impl Service {
+    /// The namespace of requests to this service, in the given version of the schema.
+    pub fn namespace(&self, version: ApiVersion) -> &'static str {
+        match self {
+            Service::Ldbsv => version.namespace(),
+            Service::Reference => REFERENCE_NAMESPACE,
+        }
+    }
+
+    /// The prefix of the `SOAPAction` of requests to this service.
+    fn action(&self) -> &'static str {
+        match self {
+            Service::Ldbsv => LDBSV_ACTION,
+            Service::Reference => REFERENCE_ACTION,
+        }
+    }
+}
+
+/// Escapes text so that it can be put in an XML element or attribute.
+pub fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 8);
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Writes an element in the request namespace containing `value`, escaped.
+fn element(body: &mut String, name: &str, value: impl Display) {
+    body.push_str(&format!(
+        "<ldb:{0}>{1}</ldb:{0}>",
+        name,
+        escape(&value.to_string())
+    ));
+}
+
+/// Formats a time as the API expects, in UK time and without an offset.
+fn format_time(time: &DateTime<FixedOffset>) -> String {
+    uk_time(time).format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// A request to OpenLDBSVWS, which can be serialized to a SOAP envelope.
+pub trait SoapRequest {
+    /// The name of the operation, such as "GetServiceDetailsByRID".
+    fn operation(&self) -> &'static str;
+
+    /// The web service which handles this request.
+    fn service(&self) -> Service {
+        Service::Ldbsv
+    }
+
+    /// Writes the parameters of this request, which are the contents of its request element.
+    fn write_parameters(&self, body: &mut String);
+
+    /// Checks that this request can be sent. The error describes what is wrong with it.
+    fn validate(&self) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    /// If true, this request is for a single service given by its RID, so a fault blaming the request means the
+    /// service wasn't found.
+    fn is_service_request(&self) -> bool {
+        false
+    }
+
+    /// The `SOAPAction` header of this request.
+    fn soap_action(&self) -> String {
+        format!("{}{}", self.service().action(), self.operation())
+    }
+
+    /// Serializes this request to a SOAP envelope for the default version, authenticated with `token`.
+    fn envelope(&self, token: &str) -> String {
+        self.envelope_for(token, ApiVersion::default())
+    }
+
+    /// Serializes this request to a SOAP envelope for `version`, authenticated with `token`.
+    fn envelope_for(&self, token: &str, version: ApiVersion) -> String {
+        let mut parameters = String::new();
+
+        self.write_parameters(&mut parameters);
+
+        format!(
+            "<soapenv:Envelope xmlns:soapenv=\"{soap}\" xmlns:typ=\"{typ}\" xmlns:ldb=\"{ldb}\">\
+             <soapenv:Header><typ:AccessToken><typ:TokenValue>{token}</typ:TokenValue></typ:AccessToken>\
+             </soapenv:Header><soapenv:Body><ldb:{operation}Request>{parameters}</ldb:{operation}Request>\
+             </soapenv:Body></soapenv:Envelope>",
+            soap = SOAP_NAMESPACE,
+            typ = TOKEN_NAMESPACE,
+            ldb = self.service().namespace(version),
+            token = escape(token),
+            operation = self.operation(),
+            parameters = parameters
+        )
+    }
+}
+
+/// A `GetServiceDetailsByRID` request, for the details of a service.
+#[derive(Debug, Clone)]
+pub struct ServiceDetailsRequest<'a> {
+    /// The RTTI ID of the service.
+    pub rid: &'a str,
+}
+
+impl<'a> ServiceDetailsRequest<'a> {
+    /// Creates a request for the details of the service `rid`.
+    pub fn new(rid: &'a str) -> Self {
+        ServiceDetailsRequest { rid }
+    }
+}
+
+impl<'a> SoapRequest for ServiceDetailsRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetServiceDetailsByRID"
+    }
+
+    fn is_service_request(&self) -> bool {
+        true
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        element(body, "rid", self.rid);
+    }
+}
+
+/// A `GetHistoricServiceDetails` request, for the details of a service as they were at a point in the past.
+#[derive(Debug, Clone)]
+pub struct HistoricServiceDetailsRequest<'a> {
+    /// The RTTI ID of the service.
+    pub rid: &'a str,
+    /// The time to get the details as of.
+    pub historic: DateTime<FixedOffset>,
+}
+
+impl<'a> HistoricServiceDetailsRequest<'a> {
+    /// Creates a request for the details of the service `rid` as they were at `historic`.
+    pub fn new(rid: &'a str, historic: DateTime<FixedOffset>) -> Self {
+        HistoricServiceDetailsRequest { rid, historic }
+    }
+}
+
+impl<'a> SoapRequest for HistoricServiceDetailsRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetHistoricServiceDetails"
+    }
+
+    fn is_service_request(&self) -> bool {
+        true
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        element(body, "rid", self.rid);
+        element(body, "historicDateTime", format_time(&self.historic));
+    }
+}
+
+/// A `GetHistoricTimeLine` request, for the points in time at which a service changed.
+#[derive(Debug, Clone)]
+pub struct HistoricTimelineRequest<'a> {
+    /// The RTTI ID of the service.
+    pub rid: &'a str,
+}
+
+impl<'a> HistoricTimelineRequest<'a> {
+    /// Creates a request for the timeline of the service `rid`.
+    pub fn new(rid: &'a str) -> Self {
+        HistoricTimelineRequest { rid }
+    }
+}
+
+impl<'a> SoapRequest for HistoricTimelineRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetHistoricTimeLine"
+    }
+
+    fn is_service_request(&self) -> bool {
+        true
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        element(body, "rid", self.rid);
+    }
+}
+
+/// The station board operations, which all take the same parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardOperation {
+    /// `GetArrivalBoardByCRS`.
+    ArrivalByCrs,
+    /// `GetDepartureBoardByCRS`.
+    DepartureByCrs,
+    /// `GetArrivalDepartureBoardByCRS`.
+    ArrivalDepartureByCrs,
+    /// `GetArrivalBoardByTIPLOC`.
+    ArrivalByTiploc,
+    /// `GetDepartureBoardByTIPLOC`.
+    DepartureByTiploc,
+    /// `GetArrivalDepartureBoardByTIPLOC`.
+    ArrivalDepartureByTiploc,
+    /// `GetHistoricArrivalBoard`, for the arrival board by CRS as it was at a point in the past.
+    HistoricArrival(DateTime<FixedOffset>),
+    /// `GetHistoricDepartureBoard`, for the departure board by CRS as it was at a point in the past.
+    HistoricDeparture(DateTime<FixedOffset>),
+}
+
+impl BoardOperation {
+    /// The name of this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardOperation::ArrivalByCrs => "GetArrivalBoardByCRS",
+            BoardOperation::DepartureByCrs => "GetDepartureBoardByCRS",
+            BoardOperation::ArrivalDepartureByCrs => "GetArrivalDepartureBoardByCRS",
+            BoardOperation::ArrivalByTiploc => "GetArrivalBoardByTIPLOC",
+            BoardOperation::DepartureByTiploc => "GetDepartureBoardByTIPLOC",
+            BoardOperation::ArrivalDepartureByTiploc => "GetArrivalDepartureBoardByTIPLOC",
+            BoardOperation::HistoricArrival(_) => "GetHistoricArrivalBoard",
+            BoardOperation::HistoricDeparture(_) => "GetHistoricDepartureBoard",
+        }
+    }
+
+    /// If true, the board is requested by TIPLOC rather than CRS code.
+    pub fn by_tiploc(&self) -> bool {
+        matches!(
+            self,
+            BoardOperation::ArrivalByTiploc
+                | BoardOperation::DepartureByTiploc
+                | BoardOperation::ArrivalDepartureByTiploc
+        )
+    }
+
+    /// The time a historic board is viewed as of.
+    pub fn historic(&self) -> Option<DateTime<FixedOffset>> {
+        match self {
+            BoardOperation::HistoricArrival(historic)
+            | BoardOperation::HistoricDeparture(historic) => Some(*historic),
+            _ => None,
+        }
+    }
+}
+
+/// A station board request.
+#[derive(Debug, Clone)]
+pub struct BoardRequest<'a> {
+    /// The board to get.
+    pub operation: BoardOperation,
+    /// The CRS code or TIPLOC of the location, depending on the operation.
+    pub location: &'a str,
+    /// The options of the board.
+    pub options: BoardOptions<'a>,
+}
+
+impl<'a> BoardRequest<'a> {
+    /// Creates a request for a board of `location`, with the default options.
+    pub fn new(operation: BoardOperation, location: &'a str) -> Self {
+        BoardRequest {
+            operation,
+            location,
+            options: BoardOptions::default(),
+        }
+    }
+
+    /// Sets the options of the board.
+    pub fn with_options(mut self, options: BoardOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The time the board is requested for, with the offset applied. Historic boards default to the time they are
+    /// viewed as of, and other boards to now.
+    fn time(&self) -> DateTime<FixedOffset> {
+        let time = self
+            .options
+            .time
+            .or_else(|| self.operation.historic())
+            .unwrap_or_else(|| uk_time(&Utc::now()));
+
+        time + Duration::minutes(self.options.time_offset.unwrap_or(0).into())
+    }
+}
+
+impl<'a> SoapRequest for BoardRequest<'a> {
+    fn operation(&self) -> &'static str {
+        self.operation.name()
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        let options = &self.options;
+        let (key, filter) = if self.operation.by_tiploc() {
+            (
+                "tiploc",
+                options.filter_tiploc.map(|code| ("filterTiploc", code)),
+            )
+        } else {
+            ("crs", options.filter_crs.map(|code| ("filterCRS", code)))
+        };
+
+        element(body, "numRows", options.num_rows.unwrap_or(150));
+        element(body, key, self.location);
+
+        if let Some(historic) = self.operation.historic() {
+            element(body, "historicDateTime", format_time(&historic));
+        }
+
+        element(body, "time", format_time(&self.time()));
+        element(body, "timeWindow", options.time_window.unwrap_or(120));
+
+        if let Some((key, code)) = filter {
+            element(body, key, code);
+            element(
+                body,
+                "filterType",
+                options.filter_type.unwrap_or(FilterType::To).as_str(),
+            );
+        }
+
+        if options.non_passenger_services {
+            element(body, "getNonPassengerServices", true);
+        }
+    }
+
+    fn validate(&self) -> Result<(), &'static str> {
+        match self.options.num_rows {
+            Some(0) => Err("no rows"),
+            Some(rows) if rows > MAX_ROWS => Err("more than 150 rows"),
+            _ => validate_time_window(&self.options),
+        }
+    }
+}
+
+/// Checks the time window of a board or departures request.
+fn validate_time_window(options: &BoardOptions) -> Result<(), &'static str> {
+    match options.time_window {
+        Some(window) if window > MAX_TIME_WINDOW => Err("a time window of more than 1440 minutes"),
+        _ => Ok(()),
+    }
+}
+
+/// The departures operations, which all take the same parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeparturesOperation {
+    /// `GetNextDeparturesByCRS`.
+    Next,
+    /// `GetFastestDeparturesByCRS`.
+    Fastest,
+    /// `GetNextDeparturesWithDetailsByCRS`.
+    NextWithDetails,
+    /// `GetFastestDeparturesWithDetailsByCRS`.
+    FastestWithDetails,
+}
+
+impl DeparturesOperation {
+    /// The name of this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeparturesOperation::Next => "GetNextDeparturesByCRS",
+            DeparturesOperation::Fastest => "GetFastestDeparturesByCRS",
+            DeparturesOperation::NextWithDetails => "GetNextDeparturesWithDetailsByCRS",
+            DeparturesOperation::FastestWithDetails => "GetFastestDeparturesWithDetailsByCRS",
+        }
+    }
+}
+
+/// A request for the next (or fastest) departure from a station to each of a list of destinations.
+#[derive(Debug, Clone)]
+pub struct DeparturesRequest<'a> {
+    /// The departures to get.
+    pub operation: DeparturesOperation,
+    /// The CRS code of the station.
+    pub crs: &'a str,
+    /// The CRS codes of the destinations. There must be between 1 and 25.
+    pub destinations: &'a [&'a str],
+    /// The options of the request. Only the time, time offset, time window and non-passenger options are used.
+    pub options: BoardOptions<'a>,
+}
+
+impl<'a> DeparturesRequest<'a> {
+    /// Creates a request for departures from `crs` to `destinations`, with the default options.
+    pub fn new(operation: DeparturesOperation, crs: &'a str, destinations: &'a [&'a str]) -> Self {
+        DeparturesRequest {
+            operation,
+            crs,
+            destinations,
+            options: BoardOptions::default(),
+        }
+    }
+
+    /// Sets the options of the request.
+    pub fn with_options(mut self, options: BoardOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a> SoapRequest for DeparturesRequest<'a> {
+    fn operation(&self) -> &'static str {
+        self.operation.name()
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        let options = &self.options;
+        let time = options.time.unwrap_or_else(|| uk_time(&Utc::now()))
+            + Duration::minutes(options.time_offset.unwrap_or(0).into());
+
+        element(body, "crs", self.crs);
+
+        body.push_str("<ldb:filterList>");
+
+        for crs in self.destinations {
+            element(body, "crs", crs);
+        }
+
+        body.push_str("</ldb:filterList>");
+
+        element(body, "time", format_time(&time));
+        element(body, "timeWindow", options.time_window.unwrap_or(120));
+
+        if options.non_passenger_services {
+            element(body, "getNonPassengerServices", true);
+        }
+    }
+
+    fn validate(&self) -> Result<(), &'static str> {
+        match self.destinations.len() {
+            0 => Err("no destinations"),
+            len if len > MAX_DESTINATIONS => Err("more than 25 destinations"),
+            _ => validate_time_window(&self.options),
+        }
+    }
+}
+
+/// A `QueryServices` request, to find services given their headcode or TSDB Train UID.
+#[derive(Debug, Clone)]
+pub struct QueryServicesRequest<'a> {
+    /// The headcode or UID of the service.
+    pub service_id: &'a str,
+    /// The Scheduled Departure Date of the service.
+    pub sdd: NaiveDate,
+    /// Filters for the services.
+    pub options: QueryOptions<'a>,
+}
+
+impl<'a> QueryServicesRequest<'a> {
+    /// Creates a request for services with the headcode or UID `service_id` departing on `sdd`.
+    pub fn new(service_id: &'a str, sdd: NaiveDate) -> Self {
+        QueryServicesRequest {
+            service_id,
+            sdd,
+            options: QueryOptions::default(),
+        }
+    }
+
+    /// Sets the filters for the services.
+    pub fn with_options(mut self, options: QueryOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a> SoapRequest for QueryServicesRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "QueryServices"
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        element(body, "serviceID", self.service_id);
+        element(body, "sdd", self.sdd.format("%Y-%m-%d"));
+
+        if let Some(time) = self.options.filter_time {
+            element(body, "filterTime", time.format("%H:%M:%S"));
+        }
+
+        if let Some(crs) = self.options.filter_crs {
+            element(body, "filterCrs", crs);
+        }
+
+        if let Some(toc) = self.options.filter_toc {
+            element(body, "filterTOC", toc);
+        }
+    }
+}
+
+/// A `GetDisruptionList` request, for the NRCC messages of one or more stations.
+#[derive(Debug, Clone)]
+pub struct DisruptionListRequest<'a> {
+    /// The CRS codes of the stations.
+    pub crs: &'a [&'a str],
+}
+
+impl<'a> DisruptionListRequest<'a> {
+    /// Creates a request for the messages of the stations `crs`.
+    pub fn new(crs: &'a [&'a str]) -> Self {
+        DisruptionListRequest { crs }
+    }
+}
+
+impl<'a> SoapRequest for DisruptionListRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetDisruptionList"
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        body.push_str("<ldb:CRSList>");
+
+        for crs in self.crs {
+            element(body, "crs", crs);
+        }
+
+        body.push_str("</ldb:CRSList>");
+    }
+
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.crs.is_empty() {
+            Err("no stations")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A `GetReasonCode` request, for the text of a reason code.
+#[derive(Debug, Clone)]
+pub struct ReasonCodeRequest {
+    /// The reason code.
+    pub code: u16,
+}
+
+impl ReasonCodeRequest {
+    /// Creates a request for the text of the reason `code`.
+    pub fn new(code: u16) -> Self {
+        ReasonCodeRequest { code }
+    }
+}
+
+impl SoapRequest for ReasonCodeRequest {
+    fn operation(&self) -> &'static str {
+        "GetReasonCode"
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        element(body, "reasonCode", self.code);
+    }
+}
+
+/// A `GetReasonCodeList` request, for the text of every reason code.
+#[derive(Debug, Clone, Default)]
+pub struct ReasonCodeListRequest;
+
+impl SoapRequest for ReasonCodeListRequest {
+    fn operation(&self) -> &'static str {
+        "GetReasonCodeList"
+    }
+
+    fn write_parameters(&self, _body: &mut String) {}
+}
+
+/// A `GetSourceInstanceNames` request, for the names of every source instance.
+#[derive(Debug, Clone, Default)]
+pub struct SourceInstanceNamesRequest;
+
+impl SoapRequest for SourceInstanceNamesRequest {
+    fn operation(&self) -> &'static str {
+        "GetSourceInstanceNames"
+    }
+
+    fn write_parameters(&self, _body: &mut String) {}
+}
+
+/// A `GetTOCList` request, for every train operating company.
+#[derive(Debug, Clone, Default)]
+pub struct TocListRequest<'a> {
+    /// The version of the list already held. If it is the latest, the response is empty.
+    pub current_version: Option<&'a str>,
+}
+
+impl<'a> TocListRequest<'a> {
+    /// Creates a request for the whole list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only gets the list if it is newer than `version`.
+    pub fn with_current_version(mut self, version: Option<&'a str>) -> Self {
+        self.current_version = version;
+        self
+    }
+}
+
+impl<'a> SoapRequest for TocListRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetTOCList"
+    }
+
+    fn service(&self) -> Service {
+        Service::Reference
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        if let Some(version) = self.current_version {
+            element(body, "currentVersion", version);
+        }
+    }
+}
+
+/// A `GetStationList` request, for every station.
+#[derive(Debug, Clone, Default)]
+pub struct StationListRequest<'a> {
+    /// The version of the list already held. If it is the latest, the response is empty.
+    pub current_version: Option<&'a str>,
+}
+
+impl<'a> StationListRequest<'a> {
+    /// Creates a request for the whole list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only gets the list if it is newer than `version`.
+    pub fn with_current_version(mut self, version: Option<&'a str>) -> Self {
+        self.current_version = version;
+        self
+    }
+}
+
+impl<'a> SoapRequest for StationListRequest<'a> {
+    fn operation(&self) -> &'static str {
+        "GetStationList"
+    }
+
+    fn service(&self) -> Service {
+        Service::Reference
+    }
+
+    fn write_parameters(&self, body: &mut String) {
+        if let Some(version) = self.current_version {
+            element(body, "currentVersion", version);
+        }
+    }
+}
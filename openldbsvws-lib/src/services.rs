@@ -1,5 +1,5 @@
+use std::borrow::Cow;
 use std::iter::Iterator;
-use std::str::from_utf8;
 
 #[cfg(feature = "pretty")]
 use ansi_term::{ANSIString, ANSIStrings, Colour::Fixed, Style};
@@ -8,11 +8,27 @@ use roxmltree::{Document, Node};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::activities::{Activities, Activity};
 use crate::associations::Association;
-use crate::parsable::{Parsable, ParsingError};
 #[cfg(feature = "pretty")]
-use crate::prettyprint::PrettyPrintable;
-use crate::{bool, child, date, name, parse, text, time};
+use crate::associations::AssociationCategory;
+use crate::category::{ServiceType, TrainCategory};
+use crate::formation::Formation;
+use crate::identifiers::{Crs, Headcode, Rid, Rsid, Tiploc, Uid};
+use crate::parsable::{
+    decoded, missing_result, owned, slice, Mode, Parsable, ParsingError, ParsingWarning,
+};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{
+    format_scheduled, PrettyOptions, PrettyPrintable, ARROW, ARROW_LEFT, CANCELLED, CIRCLE, CROSS,
+    DOTTED_CIRCLE, GREY, HERE, INDENT, LATE, LIGHT_CANCELLED, LIGHT_HERE, LIGHT_LATE, LIGHT_PASSED,
+    LIGHT_SCHEDULED, LINE, PASSED, PURPLE, SCHEDULED, SEMI_CIRCLE_1, SEMI_CIRCLE_3,
+};
+use crate::reasons::{Reason, ReasonCatalogue};
+use crate::version::ApiVersion;
+use crate::{
+    attr, bool, child, date, decoded_text, identifier, name, optional_identifier, parse, text, time,
+};
 
 mod private {
     pub trait Sealed {}
@@ -23,11 +39,45 @@ mod private {
 #[derive(Debug, Clone)]
 pub struct Location<'a> {
     /// The location's name.
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     /// The CRS code of this location.
-    pub crs: Option<&'a str>,
+    pub crs: Option<Crs<'a>>,
     /// The TIPLOC code of this location.
-    pub tiploc: Option<&'a str>,
+    pub tiploc: Option<Tiploc<'a>>,
+}
+
+impl<'a> Location<'a> {
+    /// Copies any borrowed strings, so that this location no longer borrows from the response.
+    pub fn into_owned(self) -> Location<'static> {
+        Location {
+            name: owned(self.name),
+            crs: self.crs.map(Crs::into_owned),
+            tiploc: self.tiploc.map(Tiploc::into_owned),
+        }
+    }
+}
+
+impl<'b> Location<'b> {
+    /// Parses the `locationName`, `crs` and `tiploc` children of a node. The node itself can be any element which
+    /// describes a location. In lenient mode, an invalid CRS code or TIPLOC is left out with a warning.
+    pub(crate) fn parse_with<'a>(
+        location: &Node<'a, 'a>,
+        string: &'b str,
+        mode: &mut Mode,
+    ) -> Result<Location<'b>, ParsingError<'b>> {
+        Ok(Location {
+            name: decoded_text!(string, location, "locationName")?,
+            crs: optional_identifier!(mode, string, location, "crs")?,
+            tiploc: optional_identifier!(mode, string, location, "tiploc")?,
+        })
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for Location<'b> {
+    /// Parses a location strictly. See `Location::parse_with`.
+    fn parse(location: &Node<'a, 'a>, string: &'b str) -> Result<Location<'b>, ParsingError<'b>> {
+        Location::parse_with(location, string, &mut Mode::Strict)
+    }
 }
 
 /// Forecast types.
@@ -52,14 +102,29 @@ pub enum ForecastType {
     Delayed,
 }
 
-/// This enum is returned by `ServiceTime::lateness`.
+impl ForecastType {
+    /// Parses the contents of a forecast type field, such as `arrivalType`.
+    fn from_field(typ: &str) -> Result<ForecastType, ParsingError<'_>> {
+        Ok(match typ {
+            "Forecast" => ForecastType::Estimated,
+            "Actual" => ForecastType::Actual,
+            "NoLog" => ForecastType::NoLog,
+            "NoReport" => ForecastType::NoReport,
+            "Delayed" => ForecastType::Delayed,
+
+            _ => return Err(ParsingError::InvalidForecast(typ.into())),
+        })
+    }
+}
+
+/// This enum is returned by `Lateness::lateness`.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UserLateness {
     /// If the service is early, this is returned. Note that the duration will be negative.
     ///
     /// A service which is early by a minute or less is considered "on time". See `OnTime`.
-    Early(Duration),
+    Early(#[cfg_attr(feature = "serde", serde(with = "seconds"))] Duration),
     /// If the service is on time, this is returned.
     /// Note that the duration can be negative.
     ///
@@ -68,17 +133,140 @@ pub enum UserLateness {
     ///
     /// For example, a service with a scheduled arrival of 15:00 and an actual arrival of 14:59 has "arrived on time",
     /// rather than being "1 minute early".
-    OnTime(Duration),
+    OnTime(#[cfg_attr(feature = "serde", serde(with = "seconds"))] Duration),
     /// If the service is late, this is returned. The duration cannot be negative.
     ///
     /// A service which is late by a minute or less is considered "on time". See `OnTime`.
-    Late(Duration)
+    Late(#[cfg_attr(feature = "serde", serde(with = "seconds"))] Duration),
+    /// If the service is delayed by an unknown amount, this is returned. This corresponds to `ForecastType::Delayed`,
+    /// where any estimate is uncertain and should not be shown to the public.
+    Delayed,
+}
+
+/// Serializes durations as whole seconds, as chrono can't serialize them itself.
+#[cfg(feature = "serde")]
+mod seconds {
+    use chrono::Duration;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let seconds = i64::deserialize(deserializer)?;
+
+        // Durations are stored in milliseconds, so anything bigger would overflow.
+        if (-(i64::MAX / 1000)..=i64::MAX / 1000).contains(&seconds) {
+            Ok(Duration::seconds(seconds))
+        } else {
+            Err(D::Error::custom("duration out of range"))
+        }
+    }
+
+    /// The same, for optional durations.
+    pub mod option {
+        use chrono::Duration;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match duration {
+                Some(duration) => serializer.serialize_some(&duration.num_seconds()),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            #[derive(Deserialize)]
+            struct Seconds(#[serde(with = "super")] Duration);
+
+            Ok(Option::<Seconds>::deserialize(deserializer)?.map(|x| x.0))
+        }
+    }
+}
+
+/// How early or late a service can be while still being on time.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatenessTolerance {
+    /// How early a service can be while still being on time. If None, early services are always on time.
+    #[cfg_attr(feature = "serde", serde(with = "seconds::option"))]
+    pub early: Option<Duration>,
+    /// How late a service can be while still being on time.
+    #[cfg_attr(feature = "serde", serde(with = "seconds"))]
+    pub late: Duration,
+}
+
+impl LatenessTolerance {
+    /// The default tolerance: a minute either way, to allow for times being rounded to the nearest minute.
+    pub fn minute() -> Self {
+        LatenessTolerance {
+            early: Some(Duration::minutes(1)),
+            late: Duration::minutes(1),
+        }
+    }
+
+    /// The Right Time measure: a service is on time if it is no more than 59 seconds late. Early services are on time.
+    pub fn right_time() -> Self {
+        LatenessTolerance {
+            early: None,
+            late: Duration::seconds(59),
+        }
+    }
+
+    /// The Public Performance Measure for London & South East and regional services: a service is on time if it is
+    /// no more than 5 minutes late. Early services are on time.
+    pub fn ppm_short_distance() -> Self {
+        LatenessTolerance {
+            early: None,
+            late: Duration::minutes(5),
+        }
+    }
+
+    /// The Public Performance Measure for long distance services: a service is on time if it is no more than 10
+    /// minutes late. Early services are on time.
+    pub fn ppm_long_distance() -> Self {
+        LatenessTolerance {
+            early: None,
+            late: Duration::minutes(10),
+        }
+    }
+
+    /// Classifies the difference between an actual or forecast time and its scheduled time.
+    pub fn classify(&self, difference: Duration) -> UserLateness {
+        if difference > self.late {
+            UserLateness::Late(difference)
+        } else if matches!(self.early, Some(early) if difference < -early) {
+            UserLateness::Early(difference)
+        } else {
+            UserLateness::OnTime(difference)
+        }
+    }
 }
 
-/// The lateness trait provides the `lateness()` function for ServiceTime and nothing else.
-/// This trait is sealed.
+impl Default for LatenessTolerance {
+    fn default() -> Self {
+        LatenessTolerance::minute()
+    }
+}
+
+/// The lateness trait provides the `lateness()` function for ServiceTime, ServiceLocation and ServiceDetails and
+/// nothing else. This trait is sealed.
 pub trait Lateness: private::Sealed {
-    fn lateness(&self) -> UserLateness {};
+    /// How late the service is, with the default tolerance of a minute either way. If None, the lateness is unknown,
+    /// such as when the forecast type is `NoLog` or `NoReport`.
+    fn lateness(&self) -> Option<UserLateness> {
+        self.lateness_with(&LatenessTolerance::default())
+    }
+
+    /// How late the service is, with a given tolerance. See `lateness`.
+    fn lateness_with(&self, tolerance: &LatenessTolerance) -> Option<UserLateness>;
 }
 
 /// A service time.
@@ -91,6 +279,16 @@ pub struct ServiceTime<'a> {
     /// The public scheduled time of departure of this service at this location. This may be missing if this location is
     /// the service's final destination.
     pub scheduled_departure: Option<DateTime<FixedOffset>>,
+    /// The working scheduled time of arrival of this service at this location. Working times are used by the railway
+    /// rather than the public, and can be given to the half minute. This is missing if this location is the service's
+    /// origin or is passed.
+    pub working_arrival: Option<DateTime<FixedOffset>>,
+    /// The working scheduled time of departure of this service at this location. This is missing if this location is
+    /// the service's final destination or is passed.
+    pub working_departure: Option<DateTime<FixedOffset>>,
+    /// The working scheduled time this service passes this location. This is only given if the service passes this
+    /// location without stopping.
+    pub working_pass: Option<DateTime<FixedOffset>>,
     /// The time of arrival for this service at this location. Depending on `ForecastType`, this is either:
     /// - Estimated: a forecast (corresponds to `eta`)
     /// - Actual: the actual time this train arrived (corresponds to `ata`)
@@ -119,109 +317,166 @@ pub struct ServiceTime<'a> {
     pub departure_forecast_type: Option<ForecastType>,
     /// The arrival time source of this location. This is the internal service (usually "TRUST" or "Darwin") that
     /// provided the information.
-    pub arrival_source: Option<&'a str>,
-    /// The arrival time source instance of this location. These map to codes that can be retrieved through
-    /// `GetSourceInstanceNames`. todo! implement GetSourceInstanceNames
-    pub arrival_source_instance: Option<&'a str>,
+    pub arrival_source: Option<Cow<'a, str>>,
+    /// The arrival time source instance of this location, such as "at09". These map to names, such as a specific
+    /// CIS or TD area, that can be looked up in a `SourceInstanceList` from `GetSourceInstanceNames`.
+    pub arrival_source_instance: Option<Cow<'a, str>>,
     /// The departure time source of this location. This is the internal service (usually "TRUST" or "Darwin") that
     /// provided the information.
-    pub departure_source: Option<&'a str>,
-    /// The departure time source instance of this location. These map to codes that can be retrieved through
-    /// `GetSourceInstanceNames`. todo! implement GetSourceInstanceNames
-    pub departure_source_instance: Option<&'a str>,
+    pub departure_source: Option<Cow<'a, str>>,
+    /// The departure time source instance of this location. See `arrival_source_instance`.
+    pub departure_source_instance: Option<Cow<'a, str>>,
 }
 
-/// Activity codes.
-///
-/// See [Activity Codes](https://wiki.openraildata.com//index.php?title=Activity_codes) on the
-/// Open Rail Data Wiki.
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Activity {
-    /// Stops to detach vehicles. (-D)
-    StopDetach,
-    /// Stops to attach and detach vehicles. (-T)
-    StopAttachDetach,
-    /// Stops to attach vehicles. (-U)
-    StopAttach,
-    /// Stops or shunts for other trains to pass. (A)
-    StopOrShuntForPass,
-    /// Attaches or detaches an assisting locomotive. (AE)
-    AttachOrDetachAssistingLocomotive,
-    /// Shows as 'X' on arrival. (AX)
-    ///
-    /// Nor the Open Rail Data Wiki nor the CIF User Spec provide useful information on this.
-    ShowsAsXOnArrival,
-    /// Stops for banking locomotive. (BL)
-    StopsForBankingLocomotive,
-    /// Stops to change train crew. (C)
-    StopsToChangeCrew,
-    /// Stops to set down passengers. (D)
-    ///
-    /// Passengers may not board here.
-    StopsToSetDownPassengers,
-    /// Stops for examination. (E)
-    StopsForExamination,
-    /// GBPRTT (Great British Railways Transition Team) Data to add. (G)
-    GBPRTTDataToAdd,
-    /// Notional activity to prevent WTT columns merge. (H)
-    ///
-    /// This can probably be safely treated as no activity.
-    Notional,
-    /// Notional activity to prevent WTT columns merge where 3rd column. (H)
-    ///
-    /// This can probably be safely treated as no activity.
-    NotionalActivityThirdColumn,
-    /// Passenger count point. (K)
-    PassengerCountPoint,
-    /// Ticket collection and examination point. (KC)
-    TicketCollectionAndExaminationPoint,
-    /// Ticket examination point. (KE)
-    TicketExaminationPoint,
-    /// Ticket examination point for first class only. (KF)
-    TicketExaminationPointFirstClass,
-    /// Selective ticket examination point. (KS)
-    SelectiveTicketExaminationPoint,
-    /// Stops to change locomotive. (L)
-    StopsToChangeLocomotive,
-    /// Stop not advertised. (N)
-    StopNotAdvertised,
-    /// Stops for other operating reasons. (OP)
-    StopsForOtherReasons,
-    /// Train locomotive on rear. (OR)
-    TrainLocomotiveOnRear,
-    /// Propelling between points shown. (PR)
-    PropellingBetweenPointsShown,
-    /// Stops when required. (R)
-    StopsWhenRequired,
-    /// Stops for reversing move or when the driver changes ends. (RM)
-    StopsForReversingMove,
-    /// Stops for locomotive to run round train. (RR)
-    StopsForLocomotiveToRunRoundTrain,
-    /// Stops for railway personnel only. (S)
-    StopsForRailwayPersonnel,
-    /// Stops to take up and set down passengers. (T)
-    ///
-    /// Passengers may board and exit the train.
-    StopsToTakeUpAndSetDownPassengers,
-    /// Train begins. (TB)
-    TrainBegins,
-    /// Train finishes. (TF)
-    TrainFinishes,
-    /// Activity requested for TOPS reporting purposes. (TS)
-    RequestedForTOPS,
-    /// Stops or passes for tablet, staff or token. (TW)
-    StopsOrPassesForTabletOrStaffOrToken,
-    /// Stops to take up passengers. (U)
-    ///
-    /// Passengers may not exit the train.
-    StopsToTakeUpPassengers,
-    /// Stops for watering of coaches. (W)
-    StopsForWateringOfCoaches,
-    /// Passes another train at crossing point on a single line. (X)
-    PassesAnotherTrain,
-    /// No activity.
-    None,
+impl<'a> ServiceTime<'a> {
+    /// The scheduled time of a location, depending on how it is timed. Passes only have a working pass time, and
+    /// operational locations only have working times. Otherwise, this is the public departure time, or the arrival
+    /// time if the service doesn't depart.
+    pub fn effective_scheduled(
+        &self,
+        pass: bool,
+        operational: bool,
+    ) -> Option<DateTime<FixedOffset>> {
+        if pass {
+            self.working_pass
+        } else if operational {
+            self.working_departure.or(self.working_arrival)
+        } else {
+            self.scheduled_departure
+                .or(self.scheduled_arrival)
+                .or(self.working_departure)
+                .or(self.working_arrival)
+        }
+    }
+
+    /// The working scheduled time of a location: the pass time, or the departure time, or the arrival time if the
+    /// service doesn't depart.
+    pub fn working_scheduled(&self) -> Option<DateTime<FixedOffset>> {
+        self.working_pass
+            .or(self.working_departure)
+            .or(self.working_arrival)
+    }
+
+    /// The forecast or actual time matching `effective_scheduled`: the departure time where the service departs or
+    /// passes, otherwise the arrival time.
+    pub fn effective_actual(&self) -> Option<DateTime<FixedOffset>> {
+        let departs = self
+            .scheduled_departure
+            .or(self.working_departure)
+            .or(self.working_pass)
+            .is_some();
+
+        if departs {
+            self.departure
+        } else {
+            self.arrival
+        }
+    }
+
+    /// Copies any borrowed strings, so that these times no longer borrow from the response.
+    pub fn into_owned(self) -> ServiceTime<'static> {
+        ServiceTime {
+            scheduled_arrival: self.scheduled_arrival,
+            scheduled_departure: self.scheduled_departure,
+            working_arrival: self.working_arrival,
+            working_departure: self.working_departure,
+            working_pass: self.working_pass,
+            arrival: self.arrival,
+            departure: self.departure,
+            arrival_forecast_type: self.arrival_forecast_type,
+            departure_forecast_type: self.departure_forecast_type,
+            arrival_source: self.arrival_source.map(owned),
+            arrival_source_instance: self.arrival_source_instance.map(owned),
+            departure_source: self.departure_source.map(owned),
+            departure_source_instance: self.departure_source_instance.map(owned),
+        }
+    }
+}
+
+impl<'a> private::Sealed for ServiceTime<'a> {}
+
+impl<'a> Lateness for ServiceTime<'a> {
+    /// How late the service departs or passes, or arrives if it doesn't depart. The public times are compared where
+    /// there are any, otherwise the working times.
+    fn lateness_with(&self, tolerance: &LatenessTolerance) -> Option<UserLateness> {
+        let departure = self
+            .scheduled_departure
+            .or(self.working_departure)
+            .or(self.working_pass);
+
+        let (scheduled, actual, typ) = match departure {
+            Some(scheduled) => (scheduled, self.departure, &self.departure_forecast_type),
+            None => (
+                self.scheduled_arrival.or(self.working_arrival)?,
+                self.arrival,
+                &self.arrival_forecast_type,
+            ),
+        };
+
+        match typ {
+            Some(ForecastType::Estimated) | Some(ForecastType::Actual) => {
+                Some(tolerance.classify(actual? - scheduled))
+            }
+            Some(ForecastType::Delayed) => Some(UserLateness::Delayed),
+            Some(ForecastType::NoLog) | Some(ForecastType::NoReport) | None => None,
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceTime<'b> {
+    /// Parses the times of a node. The node itself can be any element which carries times, such as a `location` or a
+    /// `service`.
+    fn parse(node: &Node<'a, 'a>, string: &'b str) -> Result<ServiceTime<'b>, ParsingError<'b>> {
+        let arrival_forecast_type = match text!(string, node, "arrivalType") {
+            Ok(typ) => Some(ForecastType::from_field(typ)?),
+            Err(_) => None,
+        };
+
+        let departure_forecast_type = match text!(string, node, "departureType") {
+            Ok(typ) => Some(ForecastType::from_field(typ)?),
+            Err(_) => None,
+        };
+
+        Ok(ServiceTime {
+            scheduled_arrival: time!(string, node, "sta").ok(),
+            scheduled_departure: time!(string, node, "std").ok(),
+            working_arrival: time!(string, node, "wta").ok(),
+            working_departure: time!(string, node, "wtd").ok(),
+            working_pass: time!(string, node, "wtp").ok(),
+            arrival: match &arrival_forecast_type {
+                Some(typ) => match typ {
+                    ForecastType::Estimated => time!(string, node, "eta").ok(),
+                    ForecastType::Actual => time!(string, node, "ata").ok(),
+                    ForecastType::NoLog => None,
+                    ForecastType::NoReport => None,
+                    ForecastType::Delayed => time!(string, node, "eta").ok(),
+                },
+                None => None,
+            },
+            departure: match &departure_forecast_type {
+                Some(typ) => match typ {
+                    ForecastType::Estimated => time!(string, node, "etd").ok(),
+                    ForecastType::Actual => time!(string, node, "atd").ok(),
+                    ForecastType::NoLog => None,
+                    ForecastType::NoReport => None,
+                    ForecastType::Delayed => time!(string, node, "etd").ok(),
+                },
+                None => None,
+            },
+            arrival_forecast_type,
+            departure_forecast_type,
+            arrival_source: text!(string, node, "arrivalSource").ok().map(Cow::from),
+            arrival_source_instance: child!(node, "arrivalSource")
+                .ok()
+                .and_then(|source| attr!(string, source, "instance").ok())
+                .map(Cow::from),
+            departure_source: text!(string, node, "departureSource").ok().map(Cow::from),
+            departure_source_instance: child!(node, "departureSource")
+                .ok()
+                .and_then(|source| attr!(string, source, "instance").ok())
+                .map(Cow::from),
+        })
+    }
 }
 
 /// A location in this service's schedule. Not all locations are stopped at.
@@ -234,13 +489,15 @@ pub struct ServiceLocation<'a> {
     pub associations: Option<Vec<Association<'a>>>,
     /// Ad-hoc alerts about this stop. Relatively rare, normally reserved for significant and out of the ordinary events
     /// not well covered by other, more normal, disruption message options.
-    pub adhoc_alerts: Option<Vec<&'a str>>,
+    pub adhoc_alerts: Option<Vec<Cow<'a, str>>>,
     /// Activities that happen at this stop.
-    pub activities: Option<Vec<Activity>>,
+    pub activities: Option<Activities>,
     /// The length of the train at this stop. If None, the length is unknown.
     pub length: Option<u16>,
     /// Whether the front is detached at this stop.
     pub detach_front: bool, // someone fucked up the docs for this
+    /// The formation of the train at this stop, including how busy each coach is. If None, the formation is unknown.
+    pub formation: Option<Formation<'a>>,
     /// If true, this is an operational calling location. Arrival and departure times will be
     /// working times, rather than the usual public times.
     pub operational: bool,
@@ -276,101 +533,66 @@ pub struct ServiceLocation<'a> {
     pub time: ServiceTime<'a>,
     /// The number of seconds that this train is late. Note that this may contain text. You should use
     /// `ServiceTime::lateness` instead.
-    pub lateness: Option<&'a str>,
+    pub lateness: Option<Cow<'a, str>>,
 }
 
-impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceLocation<'b> {
-    fn parse(
+impl<'b> ServiceLocation<'b> {
+    /// Parses a location. In lenient mode, problems with activities, associations, the formation, flags and
+    /// identifiers are collected as warnings, and the location is kept without them.
+    pub(crate) fn parse_with<'a>(
         location: &Node<'a, 'a>,
         string: &'b str,
+        mode: &mut Mode,
     ) -> Result<ServiceLocation<'b>, ParsingError<'b>> {
         if name!(location) != "location" {
             return Err(ParsingError::InvalidTagName("location"));
         }
 
         Ok(ServiceLocation {
-            location: Location {
-                name: text!(string, location, "locationName")?,
-                crs: text!(string, location, "crs").ok(),
-                tiploc: text!(string, location, "tiploc").ok(),
-            },
+            location: Location::parse_with(location, string, mode).map_err(|e| e.at(location))?,
             associations: {
                 match child!(location, "associations").ok() {
                     None => None,
                     Some(associations) => {
                         let mut vec = Vec::new();
 
-                        for node in associations.children() {
-                            vec.push(Association::parse(&node, string)?)
+                        for node in associations.children().filter(|x| x.is_element()) {
+                            let association = Association::parse_with(&node, string, mode);
+
+                            if let Some(association) = mode.recover(&node, association)? {
+                                vec.push(association)
+                            }
                         }
 
                         Some(vec)
                     }
                 }
             },
-            adhoc_alerts: child!(location, "adhocAlerts")
-                .ok()
-                .and_then(|alert| todo!()),
+            adhoc_alerts: child!(location, "adhocAlerts").ok().map(|alerts| {
+                alerts
+                    .children()
+                    .filter(|x| x.has_tag_name("adhocAlertText"))
+                    .filter_map(|alert| {
+                        let text = alert.first_child().filter(|x| x.is_text())?;
+
+                        Some(decoded(slice(string, text.range()), text.text()?))
+                    })
+                    .collect()
+            }),
             activities: {
-                match text!(string, location, "activities")? {
-                    "" => None,
-                    activities => Some({
-                        let mut ret: Vec<Activity> = Vec::new();
-
-                        for activity in activities
-                            .as_bytes()
-                            .chunks(2)
-                            .map(|x| from_utf8(x).unwrap())
-                            .collect::<Vec<&str>>()
-                        {
-                            let code = match activity.trim() {
-                                "-D" => Activity::StopDetach,
-                                "-T" => Activity::StopAttachDetach,
-                                "-U" => Activity::StopAttach,
-                                "A" => Activity::StopOrShuntForPass,
-                                "AE" => Activity::AttachOrDetachAssistingLocomotive,
-                                "AX" => Activity::ShowsAsXOnArrival,
-                                "BL" => Activity::StopsForBankingLocomotive,
-                                "C" => Activity::StopsToChangeCrew,
-                                "D" => Activity::StopsToSetDownPassengers,
-                                "E" => Activity::StopsForExamination,
-                                "G" => Activity::GBPRTTDataToAdd,
-                                "H" => Activity::Notional,
-                                "HH" => Activity::NotionalActivityThirdColumn,
-                                "K" => Activity::PassengerCountPoint,
-                                "KC" => Activity::TicketCollectionAndExaminationPoint,
-                                "KE" => Activity::TicketExaminationPoint,
-                                "KF" => Activity::TicketExaminationPointFirstClass,
-                                "KS" => Activity::SelectiveTicketExaminationPoint,
-                                "L" => Activity::StopsToChangeLocomotive,
-                                "N" => Activity::StopNotAdvertised,
-                                "OP" => Activity::StopsForOtherReasons,
-                                "OR" => Activity::TrainLocomotiveOnRear,
-                                "PR" => Activity::PropellingBetweenPointsShown,
-                                "R" => Activity::StopsWhenRequired,
-                                "RM" => Activity::StopsForReversingMove,
-                                "RR" => Activity::StopsForLocomotiveToRunRoundTrain,
-                                "S" => Activity::StopsForRailwayPersonnel,
-                                "T" => Activity::StopsToTakeUpAndSetDownPassengers,
-                                "TB" => Activity::TrainBegins,
-                                "TF" => Activity::TrainFinishes,
-                                "TS" => Activity::RequestedForTOPS,
-                                "TW" => Activity::StopsOrPassesForTabletOrStaffOrToken,
-                                "U" => Activity::StopsToTakeUpPassengers,
-                                "W" => Activity::StopsForWateringOfCoaches,
-                                "X" => Activity::PassesAnotherTrain,
-                                "" => Activity::None,
-
-                                x => return Err(ParsingError::InvalidActivity(x)),
-                            };
-
-                            ret.push(code);
-                        }
+                let mut ret = Vec::new();
+
+                for code in Activities::codes(text!(string, location, "activities").unwrap_or("")) {
+                    let activity = code
+                        .parse::<Activity>()
+                        .map_err(|_| ParsingError::InvalidActivity(code.into()));
 
-                        ret.dedup_by(|a, _| *a == Activity::None);
-                        ret
-                    }),
+                    if let Some(activity) = mode.recover(location, activity)? {
+                        ret.push(activity);
+                    }
                 }
+
+                Some(Activities::from(ret)).filter(|activities| !activities.is_empty())
             },
             length: {
                 match parse!(string, location, "length", u16) {
@@ -384,121 +606,293 @@ impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceLocation<'b> {
                     Err(_) => None,
                 }
             },
-            detach_front: bool!(string, location, "detachFront", false)?,
-            operational: bool!(string, location, "isOperational", false)?,
-            pass: bool!(string, location, "isPass", false)?,
-            cancelled: bool!(string, location, "isCancelled", false)?,
-            false_destination: text!(string, location, "falseDest")
-                .ok()
-                .map(|name| Location {
+            detach_front: mode
+                .recover(location, bool!(string, location, "detachFront", false))?
+                .unwrap_or(false),
+            // Versions without formations may still have the elements, but they aren't meaningful.
+            formation: if ApiVersion::detect(location)
+                .unwrap_or_default()
+                .has_formations()
+            {
+                mode.recover(location, Formation::parse(location, string))?
+                    .filter(|formation| {
+                        !formation.coaches.is_empty() || formation.loading.is_some()
+                    })
+            } else {
+                None
+            },
+            operational: mode
+                .recover(location, bool!(string, location, "isOperational", false))?
+                .unwrap_or(false),
+            pass: mode
+                .recover(location, bool!(string, location, "isPass", false))?
+                .unwrap_or(false),
+            cancelled: mode
+                .recover(location, bool!(string, location, "isCancelled", false))?
+                .unwrap_or(false),
+            false_destination: match decoded_text!(string, location, "falseDest") {
+                Ok(name) => Some(Location {
                     name,
                     crs: None,
-                    tiploc: text!(string, location, "fdTiploc").ok(),
+                    tiploc: optional_identifier!(mode, string, location, "fdTiploc")?,
                 }),
+                Err(_) => None,
+            },
             platform: parse!(string, location, "platform", u8).ok(),
-            platform_hidden: bool!(string, location, "platformIsHidden", false)?,
+            platform_hidden: mode
+                .recover(location, bool!(string, location, "platformIsHidden", false))?
+                .unwrap_or(false),
             // The docs make this misspelling. Is it a mistake? Who knows!
-            suppressed: bool!(string, location, "serviceIsSupressed", false)?,
-            time: {
-                let arrival_forecast_type: Option<ForecastType> = match text!(string, location, "arrivalType") {
-                    Ok(typ) => match typ {
-                        "Forecast" => Some(ForecastType::Forecast),
-                        "Actual" => Some(ForecastType::Actual),
-                        "NoLog" => Some(ForecastType::NoLog),
-                        "NoReport" => Some(ForecastType::NoReport),
-                        "Delayed" => Some(ForecastType::Delayed),
-
-                        _ => Err(ParsingError::InvalidForecast(typ))?
-                    },
-                    Err(_) => None
-                };
+            suppressed: mode
+                .recover(
+                    location,
+                    bool!(string, location, "serviceIsSupressed", false),
+                )?
+                .unwrap_or(false),
+            time: ServiceTime::parse_located(location, string)?,
 
-                let departure_forecast_type: Option<ForecastType> = match text!(string, location, "departureType") {
-                    Ok(typ) => match typ {
-                        "Forecast" => Some(ForecastType::Forecast),
-                        "Actual" => Some(ForecastType::Actual),
-                        "NoLog" => Some(ForecastType::NoLog),
-                        "NoReport" => Some(ForecastType::NoReport),
-                        "Delayed" => Some(ForecastType::Delayed),
+            #[allow(deprecated)]
+            lateness: text!(string, location, "lateness").ok().map(Cow::from),
+        })
+    }
+}
 
-                        _ => Err(ParsingError::InvalidForecast(typ))?
-                    },
-                    Err(_) => None
-                };
+impl<'a, 'b> Parsable<'a, 'a, 'b> for ServiceLocation<'b> {
+    fn parse(
+        location: &Node<'a, 'a>,
+        string: &'b str,
+    ) -> Result<ServiceLocation<'b>, ParsingError<'b>> {
+        ServiceLocation::parse_with(location, string, &mut Mode::Strict)
+    }
+}
 
-                Ok(ServiceTime {
-                    scheduled_arrival: time!(string, location, "sta").ok(),
-                    scheduled_departure: time!(string, location, "std").ok(),
-                    arrival: {
-                        match arrival_forecast_type {
-                            Some(typ) => match typ {
-                                ForecastType::Estimated => time!(string, location, "eta").ok(),
-                                ForecastType::Actual => time!(string, location, "ata").ok(),
-                                ForecastType::NoLog => None,
-                                ForecastType::NoReport => None,
-                                ForecastType::Delayed => time!(string, location, "eta").ok(),
-                            },
-                            None => None,
-                        }
-                    },
-                    departure: None,
-                    arrival_forecast_type,
-                    departure_forecast_type,
-                    arrival_source: None,
-                    arrival_source_instance: None,
-                    departure_source: None,
-                    departure_source_instance: None
-                })
-            }?,
+impl<'a> private::Sealed for ServiceLocation<'a> {}
 
-            #[allow(deprecated)]
-            lateness: text!(string, location, "lateness").ok(),
+impl<'a> Lateness for ServiceLocation<'a> {
+    /// How late the service is at this location. See `ServiceTime::lateness`. Cancelled locations have no lateness.
+    fn lateness_with(&self, tolerance: &LatenessTolerance) -> Option<UserLateness> {
+        if self.cancelled {
+            return None;
         }
+
+        self.time.lateness_with(tolerance)
     }
 }
 
-const GREY: u8 = 247;
-const PASSED: u8 = 81;
-const LIGHT_PASSED: u8 = 195;
-const HERE: u8 = 155;
-const LIGHT_HERE: u8 = 193;
-const LATE: u8 = 220;
-const LIGHT_LATE: u8 = 230;
-const CANCELLED: u8 = 203;
-const LIGHT_CANCELLED: u8 = 218;
-const SCHEDULED: u8 = 183;
-const LIGHT_SCHEDULED: u8 = 225;
-
-const INDENT: &str = "    ";
-const CIRCLE: &str = "●";
-const LINE: &str = "│";
-const ARROW: &str = "⟶";
-const ARROW_LEFT: &str = "⟵";
-const CROSS: &str = "⨯";
-const DOTTED_CIRCLE: &str = "◯";
-const SEMI_CIRCLE_1: &str = "◔";
-const SEMI_CIRCLE_3: &str = "◕";
+impl<'a> ServiceLocation<'a> {
+    /// Copies any borrowed strings, so that this location no longer borrows from the response.
+    pub fn into_owned(self) -> ServiceLocation<'static> {
+        ServiceLocation {
+            location: self.location.into_owned(),
+            associations: self.associations.map(|associations| {
+                associations
+                    .into_iter()
+                    .map(Association::into_owned)
+                    .collect()
+            }),
+            adhoc_alerts: self
+                .adhoc_alerts
+                .map(|alerts| alerts.into_iter().map(owned).collect()),
+            activities: self.activities,
+            length: self.length,
+            detach_front: self.detach_front,
+            formation: self.formation.map(Formation::into_owned),
+            operational: self.operational,
+            pass: self.pass,
+            cancelled: self.cancelled,
+            false_destination: self.false_destination.map(Location::into_owned),
+            platform: self.platform,
+            platform_hidden: self.platform_hidden,
+            suppressed: self.suppressed,
+            time: self.time.into_owned(),
+            #[allow(deprecated)]
+            lateness: self.lateness.map(owned),
+        }
+    }
+
+    /// The scheduled time of this location, depending on whether it is passed or operational. See
+    /// `ServiceTime::effective_scheduled`.
+    pub fn effective_scheduled(&self) -> Option<DateTime<FixedOffset>> {
+        self.time.effective_scheduled(self.pass, self.operational)
+    }
+}
 
 #[cfg(feature = "pretty")]
 impl<'a> PrettyPrintable for ServiceLocation<'a> {
-    fn pretty(&self) -> String {}
+    fn pretty(&self) -> String {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let time = &self.time;
+
+        let departed = matches!(time.departure_forecast_type, Some(ForecastType::Actual));
+        let arrived = matches!(time.arrival_forecast_type, Some(ForecastType::Actual));
+        let unknown = matches!(
+            time.departure_forecast_type
+                .as_ref()
+                .or(time.arrival_forecast_type.as_ref()),
+            Some(ForecastType::NoLog) | Some(ForecastType::NoReport)
+        );
+
+        let scheduled = if options.working {
+            time.working_scheduled()
+        } else {
+            self.effective_scheduled()
+        };
+        let actual = time.effective_actual();
+
+        let late = matches!(
+            self.lateness(),
+            Some(UserLateness::Late(_)) | Some(UserLateness::Delayed)
+        );
+
+        let (symbol, colour, light) = if self.cancelled {
+            (CROSS, CANCELLED, LIGHT_CANCELLED)
+        } else if self.pass {
+            (DOTTED_CIRCLE, GREY, GREY)
+        } else if departed {
+            (CIRCLE, PASSED, LIGHT_PASSED)
+        } else if arrived {
+            (SEMI_CIRCLE_3, HERE, LIGHT_HERE)
+        } else if unknown {
+            (SEMI_CIRCLE_1, PASSED, LIGHT_PASSED)
+        } else if late {
+            (CIRCLE, LATE, LIGHT_LATE)
+        } else {
+            (CIRCLE, SCHEDULED, LIGHT_SCHEDULED)
+        };
+
+        let mut strings: Vec<ANSIString> = vec![
+            Style::default().paint(INDENT),
+            Fixed(colour).paint(symbol),
+            Style::default().paint(" "),
+            Fixed(light).paint(format_scheduled(scheduled, options.working)),
+            Style::default().paint(" "),
+            Fixed(colour).paint(match actual {
+                Some(actual) => actual.format("%H:%M").to_string(),
+                None => "     ".to_string(),
+            }),
+            Style::default().paint(" "),
+            if self.pass {
+                Fixed(GREY).paint(&*self.location.name)
+            } else {
+                Fixed(colour).bold().paint(&*self.location.name)
+            },
+        ];
+
+        if let Some(crs) = &self.location.crs {
+            strings.push(Fixed(GREY).paint(format!(" ({})", crs)));
+        }
+
+        if let Some(platform) = self.platform {
+            strings.push(Fixed(light).paint(format!(" platform {}", platform)));
+
+            if self.platform_hidden {
+                strings.push(Fixed(GREY).paint(" (hidden)"));
+            }
+        }
+
+        if self.cancelled {
+            strings.push(Fixed(CANCELLED).bold().paint(" cancelled"));
+        }
+
+        // Show where actual times came from, as it isn't always obvious.
+        let (source, instance) = if departed {
+            (&time.departure_source, &time.departure_source_instance)
+        } else if arrived {
+            (&time.arrival_source, &time.arrival_source_instance)
+        } else {
+            (&None, &None)
+        };
+
+        if let Some(source) = source.as_ref().filter(|source| !source.is_empty()) {
+            strings.push(Fixed(GREY).paint(match instance {
+                Some(instance) => format!(" via {} ({})", source, instance),
+                None => format!(" via {}", source),
+            }));
+        }
+
+        strings.push(Style::default().paint("\n"));
+
+        if let Some(false_destination) = &self.false_destination {
+            strings.push(Style::default().paint(INDENT));
+            strings.push(Fixed(GREY).paint(LINE));
+            strings.push(Fixed(GREY).paint(format!(
+                "{}{}shown as terminating at {}\n",
+                INDENT, INDENT, false_destination.name
+            )));
+        }
+
+        for alert in self.adhoc_alerts.iter().flatten() {
+            strings.push(Style::default().paint(INDENT));
+            strings.push(Fixed(GREY).paint(LINE));
+            strings.push(Fixed(LATE).paint(format!("{}{}{}\n", INDENT, INDENT, alert)));
+        }
+
+        let mut ret = ANSIStrings(&strings).to_string();
+
+        if let Some(formation) = &self.formation {
+            ret.push_str(&formation.pretty());
+        }
+
+        let mut strings: Vec<ANSIString> = Vec::new();
+
+        for association in self.associations.iter().flatten() {
+            let (arrow, text, other) = match association.category {
+                AssociationCategory::Join => (ARROW_LEFT, "joined by", &association.origin),
+                AssociationCategory::Divide => (ARROW, "divides to form", &association.destination),
+                AssociationCategory::Next => (ARROW, "next works", &association.destination),
+            };
+
+            strings.push(Style::default().paint(INDENT));
+            strings.push(Fixed(GREY).paint(LINE));
+            strings.push(Style::default().paint(INDENT));
+            strings.push(
+                Fixed(if association.cancelled {
+                    CANCELLED
+                } else {
+                    GREY
+                })
+                .paint(format!(
+                    "{} {} {} ({}){}{}\n",
+                    arrow,
+                    text,
+                    association.trainid,
+                    association.rid,
+                    match other {
+                        Some(other) => format!(", {}", other.name),
+                        None => String::new(),
+                    },
+                    if association.cancelled {
+                        ", cancelled"
+                    } else {
+                        ""
+                    }
+                )),
+            );
+        }
+
+        ret.push_str(&ANSIStrings(&strings).to_string());
+        ret
+    }
 }
 
 /// Details of a train service.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ServiceDetails<'b> {
     /// The time these details were generated.
     pub generated_at: DateTime<FixedOffset>,
     /// A unique RTTI ID for this service that can be used to obtain full details of the service.
-    pub rid: &'b str,
+    pub rid: Rid<'b>,
     /// The TSDB Train UID value for this service, or if one is not available, then an RTTI
     /// allocated replacement.
-    pub uid: &'b str,
+    pub uid: Uid<'b>,
     /// The Retail Service ID of the service, if known.
-    pub rsid: Option<&'b str>,
+    pub rsid: Option<Rsid<'b>>,
     /// The Train ID value (headcode) for this service.
-    pub trainid: &'b str,
+    pub trainid: Headcode<'b>,
     /// The Scheduled Departure Data of this service.
     pub sdd: NaiveDate,
     /// If true, this is a passenger service. Non-passenger services should not be published to the
@@ -506,174 +900,291 @@ pub struct ServiceDetails<'b> {
     pub passenger_service: bool,
     /// If true, this is a charter service.
     pub charter: bool,
+    /// Whether this service is a train, bus or ferry.
+    pub service_type: ServiceType,
     /// The category of this service.
-    pub category: &'b str,
+    pub category: TrainCategory,
     /// The operator of this service.
-    pub operator: &'b str,
+    pub operator: Cow<'b, str>,
     /// The operator code of this service.
-    pub operator_code: &'b str,
+    pub operator_code: Cow<'b, str>,
     /// The cancellation reason, which is not always provided.
-    pub cancel_reason: Option<&'b str>,
+    pub cancel_reason: Option<Reason<'b>>,
     /// The delay reason, which is not always provided.
-    pub delay_reason: Option<&'b str>,
+    pub delay_reason: Option<Reason<'b>>,
     /// If true, this service is operating in the reverse of its normal formation.
     pub reverse_formation: bool,
     /// The list of the locations in this service's schedule.
     pub locations: Vec<ServiceLocation<'b>>,
+    /// The version of the schema of the response these details were parsed from. If None, the response had no
+    /// namespace of a known version.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub version: Option<ApiVersion>,
+    /// Problems found while parsing leniently with `parse_lenient`. Always empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<ParsingWarning>,
 }
 
-impl<'a, 'b> TryFrom<&'a str> for ServiceDetails<'a>
-where
-    'a: 'b,
-{
-    type Error = ParsingError<'a>;
+impl<'a> private::Sealed for ServiceDetails<'a> {}
 
-    fn try_from(string: &'a str) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
+impl<'a> Lateness for ServiceDetails<'a> {
+    /// The current delay of the service: how late it was at the last location it has been reported at. If None, the
+    /// service hasn't been reported anywhere yet.
+    fn lateness_with(&self, tolerance: &LatenessTolerance) -> Option<UserLateness> {
+        self.locations
+            .iter()
+            .rev()
+            .find(|location| {
+                !location.cancelled
+                    && (matches!(
+                        location.time.departure_forecast_type,
+                        Some(ForecastType::Actual)
+                    ) || matches!(
+                        location.time.arrival_forecast_type,
+                        Some(ForecastType::Actual)
+                    ))
+            })
+            .and_then(|location| {
+                let time = &location.time;
+
+                // The departure may still be a forecast if the service has only arrived.
+                let (scheduled, actual) = match time.departure_forecast_type {
+                    Some(ForecastType::Actual) => (
+                        time.scheduled_departure
+                            .or(time.working_departure)
+                            .or(time.working_pass)?,
+                        time.departure?,
+                    ),
+                    _ => (
+                        time.scheduled_arrival.or(time.working_arrival)?,
+                        time.arrival?,
+                    ),
+                };
+
+                Some(tolerance.classify(actual - scheduled))
+            })
+    }
+}
+
+impl<'a> ServiceDetails<'a> {
+    /// Resolves the cancellation and delay reasons of this service into text, naming their location if it is in the
+    /// schedule.
+    pub fn resolve_reasons(&mut self, catalogue: &ReasonCatalogue) {
+        let locations = &self.locations;
+        let name = |reason: &Reason| {
+            locations
+                .iter()
+                .find(|x| {
+                    x.location.tiploc.is_some()
+                        && x.location.tiploc.as_deref() == reason.tiploc.as_deref()
+                })
+                .map(|x| &*x.location.name)
+        };
+
+        if let Some(reason) = &mut self.cancel_reason {
+            reason.text = catalogue.resolve_cancellation(reason, name(reason));
+        }
+
+        if let Some(reason) = &mut self.delay_reason {
+            reason.text = catalogue.resolve_late(reason, name(reason));
+        }
+    }
+
+    /// Copies any borrowed strings, so that these details no longer borrow from the response. The owned details can
+    /// be cached or sent to another task once the response is gone.
+    pub fn into_owned(self) -> ServiceDetails<'static> {
+        ServiceDetails {
+            generated_at: self.generated_at,
+            rid: self.rid.into_owned(),
+            uid: self.uid.into_owned(),
+            rsid: self.rsid.map(Rsid::into_owned),
+            trainid: self.trainid.into_owned(),
+            sdd: self.sdd,
+            passenger_service: self.passenger_service,
+            charter: self.charter,
+            service_type: self.service_type,
+            category: self.category,
+            operator: owned(self.operator),
+            operator_code: owned(self.operator_code),
+            cancel_reason: self.cancel_reason.map(Reason::into_owned),
+            delay_reason: self.delay_reason.map(Reason::into_owned),
+            reverse_formation: self.reverse_formation,
+            locations: self
+                .locations
+                .into_iter()
+                .map(ServiceLocation::into_owned)
+                .collect(),
+            version: self.version,
+            warnings: self.warnings,
+        }
+    }
+}
+
+impl<'a> ServiceDetails<'a> {
+    /// Parses service details leniently. Unlike `try_from`, problems which don't spoil the whole service, such as an
+    /// unknown activity code or a broken location, are collected in `warnings` rather than failing, and whatever they
+    /// were in is left out. Invalid identifiers, such as a RID which isn't 15 digits, are kept as they are with a
+    /// warning, but missing ones still fail.
+    pub fn parse_lenient(string: &'a str) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
+        let mut warnings = Vec::new();
+        let mut details = ServiceDetails::parse_with(string, &mut Mode::Lenient(&mut warnings))?;
+
+        details.warnings = warnings;
+
+        Ok(details)
+    }
+
+    /// Finds and parses the result of a response.
+    fn parse_with(
+        string: &'a str,
+        mode: &mut Mode,
+    ) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
         let document =
             Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
 
+        // Historic snapshots have the same contents as live details, just under a different name.
         let details = document
             .root()
             .descendants()
-            .find(|x| x.has_tag_name("GetServiceDetailsResult"))
-            .ok_or(ParsingError::MissingField("GetServiceDetailsResult"))?;
-
-        if name!(details) != "GetServiceDetailsResult" {
-            return Err(ParsingError::InvalidTagName("GetServiceDetailsResult"));
-        }
-
-        let typ = text!(string, details, "serviceType")?;
+            .find(|x| {
+                x.has_tag_name("GetServiceDetailsResult")
+                    || x.has_tag_name("GetHistoricServiceDetailsResult")
+            })
+            .ok_or_else(|| missing_result(&document, "GetServiceDetailsResult"))?;
 
-        if typ != "train" {
-            return Err(ParsingError::UnsupportedServiceType(typ));
-        }
+        ServiceDetails::from_result(&details, string, mode).map_err(|e| e.at(&details))
+    }
 
+    fn from_result<'d>(
+        details: &Node<'d, 'd>,
+        string: &'a str,
+        mode: &mut Mode,
+    ) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
         Ok(ServiceDetails {
             generated_at: time!(string, details, "generatedAt")?,
-            rid: text!(string, details, "rid")?,
-            uid: text!(string, details, "uid")?,
-            rsid: text!(string, details, "rsid").ok(),
-            trainid: text!(string, details, "trainid")?,
+            rid: identifier!(mode, string, details, "rid")?,
+            uid: identifier!(mode, string, details, "uid")?,
+            rsid: optional_identifier!(mode, string, details, "rsid")?,
+            trainid: identifier!(mode, string, details, "trainid")?,
             sdd: date!(string, details, "sdd")?,
-            passenger_service: bool!(string, details, "isPassengerService", true)?,
-            charter: bool!(string, details, "isCharter", false)?,
-            category: text!(string, details, "category")?,
-            operator: text!(string, details, "operator")?,
-            operator_code: text!(string, details, "operatorCode")?,
-            cancel_reason: text!(string, details, "cancelReason").ok(),
-            delay_reason: text!(string, details, "delayReason").ok(),
-            reverse_formation: bool!(string, details, "isReverseFormation", false)?,
+            passenger_service: mode
+                .recover(details, bool!(string, details, "isPassengerService", true))?
+                .unwrap_or(true),
+            charter: mode
+                .recover(details, bool!(string, details, "isCharter", false))?
+                .unwrap_or(false),
+            service_type: {
+                let typ = text!(string, details, "serviceType")?;
+
+                typ.parse::<ServiceType>()
+                    .map_err(|_| ParsingError::UnsupportedServiceType(typ.into()))?
+            },
+            category: text!(string, details, "category")?.into(),
+            operator: decoded_text!(string, details, "operator")?,
+            operator_code: text!(string, details, "operatorCode")?.into(),
+            cancel_reason: match child!(details, "cancelReason") {
+                Ok(reason) => mode.recover(&reason, Reason::parse(&reason, string))?,
+                Err(_) => None,
+            },
+            delay_reason: match child!(details, "delayReason") {
+                Ok(reason) => mode.recover(&reason, Reason::parse(&reason, string))?,
+                Err(_) => None,
+            },
+            reverse_formation: mode
+                .recover(details, bool!(string, details, "isReverseFormation", false))?
+                .unwrap_or(false),
             locations: {
                 let mut vec = Vec::new();
 
-                for node in child!(details, "locations")?.children() {
-                    vec.push(ServiceLocation::parse(&node, string)?)
+                for node in child!(details, "locations")?
+                    .children()
+                    .filter(|x| x.is_element())
+                {
+                    let location = ServiceLocation::parse_with(&node, string, mode);
+
+                    if let Some(location) = mode.recover(&node, location)? {
+                        vec.push(location)
+                    }
                 }
 
                 vec
             },
+            version: ApiVersion::detect(details),
+            warnings: Vec::new(),
         })
     }
 }
 
-const PURPLE: u8 = 140;
+impl<'a> TryFrom<&'a str> for ServiceDetails<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<ServiceDetails<'a>, ParsingError<'a>> {
+        ServiceDetails::parse_with(string, &mut Mode::Strict)
+    }
+}
 
 #[cfg(feature = "pretty")]
 impl<'a> PrettyPrintable for ServiceDetails<'a> {
     fn pretty(&self) -> String {
-        let strings: &[ANSIString<'a>] = &[
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    fn pretty_with(&self, options: &PrettyOptions) -> String {
+        let strings: &[ANSIString] = &[
             Style::default().paint("Service "),
-            Style::default().bold().paint(self.rid),
+            Style::default().bold().paint(&*self.rid),
             Fixed(GREY).paint("\nTSDB "),
-            Fixed(GREY).bold().paint(self.uid),
+            Fixed(GREY).bold().paint(&*self.uid),
             Fixed(GREY).paint("\nRSID "),
-            Fixed(GREY).bold().paint(self.rsid.unwrap_or("unknown")),
+            Fixed(GREY)
+                .bold()
+                .paint(self.rsid.as_deref().unwrap_or("unknown")),
             Fixed(GREY).paint("\nHeadcode "),
-            Fixed(GREY).bold().paint(self.trainid),
+            Fixed(GREY).bold().paint(&*self.trainid),
             Fixed(GREY).paint("\nDeparts "),
             Fixed(PURPLE).bold().paint(self.sdd.to_string()),
             Fixed(GREY).paint("\nType "),
-            Fixed(PURPLE).bold().paint(match self.category {
-                // https://wiki.openraildata.com/index.php?title=CIF_Codes
-                "OL" => "London Underground/Metro Service",
-                "OU" => "Unadvertised Ordinary Passenger",
-                "OO" => "Ordinary Passenger",
-                "OS" => "Staff Train",
-
-                "XC" => "Channel Tunnel",
-                "XD" => "Sleeper",
-                "XI" => "International",
-                "XR" => "Motorail",
-                "XU" => "Unadvertised Express",
-                "XX" => "Express Passenger",
-                "XZ" => "Sleeper (Domestic)",
-
-                "BR" => "Rail replacement bus",
-                "BS" => "Bus",
-                "SS" => "Ship",
-
-                "EE" => "Empty Coaching Stock (ECS)",
-                "EL" => "ECS, London Underground/Metro Service",
-                "ES" => "ECS and Staff",
-
-                "JJ" => "Postal",
-                "PM" => "Post Office Controlled Parcels",
-                "PP" => "Parcels",
-                "PV" => "Empty NPCCS",
-
-                "DD" => "Departmental",
-                "DH" => "Civil Engineer",
-                "DI" => "Mechanical & Electrical Engineer",
-                "DQ" => "Stores",
-                "DT" => "Test",
-                "DY" => "Signal & Telecommunications Engineer",
-
-                "ZB" => "Locomotive & Brake Van",
-                "ZZ" => "Light Locomotive",
-
-                "J2" => "RfD Automotive (Components)",
-                "H2" => "RfD Automotive (Vehicles)",
-                "J3" => "RfD Edible Products (UK Contracts)",
-                "J4" => "RfD Industrial Minerals (UK Contracts)",
-                "J5" => "RfD Chemicals (UK Contracts)",
-                "J6" => "RfD Building Materials (UK Contracts)",
-                "J8" => "RfD General Merchandise (UK Contracts)",
-                "H8" => "RfD European",
-                "J9" => "RfD Freightliner (Contracts)",
-                "H9" => "RfD Freightliner (Other)",
-
-                "A0" => "Coal (Distributive)",
-                "E0" => "Coal (Electricity) MGR",
-                "B0" => "Coal (Other) and Nuclear",
-                "B1" => "Metals",
-                "B4" => "Aggregates",
-                "B5" => "Domestic and Industrial Waste",
-                "B6" => "Building Materials (TLF)",
-                "B7" => "Petroleum Products",
-
-                "H0" => "RfD European Channel Tunnel (Mixed Business)",
-                "H1" => "RfD European Channel Tunnel Intermodal",
-                "H3" => "RfD European Channel Tunnel Automotive",
-                "H4" => "RfD European Channel Tunnel Contract Services",
-                "H5" => "RfD European Channel Tunnel Haulmark",
-                "H6" => "RfD European Channel Tunnel Joint Venture",
-
-                &_ => "unknown",
-            }),
+            Fixed(PURPLE).bold().paint(self.category.description()),
             Fixed(GREY).paint(format!(" ({})", self.category)),
             Fixed(GREY).paint("\nOperated by "),
-            Fixed(PURPLE).bold().paint(self.operator),
+            Fixed(PURPLE).bold().paint(&*self.operator),
             Fixed(GREY).paint(format!(" ({})", self.operator_code)),
-            Style::default().paint("\n\n"),
         ];
 
+        let mut reasons: Vec<ANSIString> = Vec::new();
+
+        match self.lateness() {
+            Some(UserLateness::Late(late)) => reasons
+                .push(Fixed(LATE).paint(format!("\nRunning {} min late", late.num_minutes()))),
+            Some(UserLateness::Early(early)) => reasons
+                .push(Fixed(PASSED).paint(format!("\nRunning {} min early", -early.num_minutes()))),
+            Some(UserLateness::OnTime(_)) => reasons.push(Fixed(PASSED).paint("\nRunning on time")),
+            Some(UserLateness::Delayed) | None => {}
+        }
+
+        if let Some(reason) = &self.cancel_reason {
+            reasons.push(Fixed(CANCELLED).paint("\nCancelled "));
+            reasons.push(Style::default().paint(reason.pretty()));
+        }
+
+        if let Some(reason) = &self.delay_reason {
+            reasons.push(Fixed(LATE).paint("\nDelayed "));
+            reasons.push(Style::default().paint(reason.pretty()));
+        }
+
+        reasons.push(Style::default().paint("\n\n"));
+
         let mut map = String::new();
 
         for location in &self.locations {
-            map.push_str(&location.pretty())
+            map.push_str(&location.pretty_with(options))
         }
 
         let mut ret = ANSIStrings(strings).to_string();
+        ret.push_str(&ANSIStrings(&reasons).to_string());
         ret.push_str(&map);
 
         ret
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "pretty")]
+use ansi_term::{ANSIStrings, Colour::Fixed, Style};
+use roxmltree::{Document, Node};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::parsable::{missing_result, owned, Parsable, ParsingError};
+#[cfg(feature = "pretty")]
+use crate::prettyprint::{PrettyPrintable, PURPLE};
+use crate::services::ServiceTime;
+use crate::{decoded_text, name, text};
+
+/// A source instance, as returned by `GetSourceInstanceNames`. Times given by a service carry the code of the
+/// instance which provided them, such as a specific CIS or TD area.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct SourceInstance<'a> {
+    /// The code of this instance, such as "at09".
+    pub code: Cow<'a, str>,
+    /// The name of this instance.
+    pub name: Cow<'a, str>,
+}
+
+impl<'a> SourceInstance<'a> {
+    /// Copies any borrowed strings, so that this instance no longer borrows from the response.
+    pub fn into_owned(self) -> SourceInstance<'static> {
+        SourceInstance {
+            code: owned(self.code),
+            name: owned(self.name),
+        }
+    }
+}
+
+impl<'a, 'b> Parsable<'a, 'a, 'b> for SourceInstance<'b> {
+    fn parse(instance: &Node<'a, 'a>, string: &'b str) -> Result<Self, ParsingError<'b>> {
+        if name!(instance) != "sourceInstance" {
+            return Err(ParsingError::InvalidTagName("sourceInstance"));
+        }
+
+        Ok(SourceInstance {
+            code: text!(string, instance, "code")?.into(),
+            name: decoded_text!(string, instance, "name")?,
+        })
+    }
+}
+
+/// Every source instance, as returned by `GetSourceInstanceNames`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub struct SourceInstanceList<'a> {
+    /// The source instances.
+    pub instances: Vec<SourceInstance<'a>>,
+}
+
+impl<'a> SourceInstanceList<'a> {
+    /// Copies any borrowed strings, so that this list no longer borrows from the response.
+    pub fn into_owned(self) -> SourceInstanceList<'static> {
+        SourceInstanceList {
+            instances: self
+                .instances
+                .into_iter()
+                .map(SourceInstance::into_owned)
+                .collect(),
+        }
+    }
+
+    /// Looks up the name of a source instance given its code.
+    pub fn name(&self, code: &str) -> Option<&str> {
+        self.instances
+            .iter()
+            .find(|instance| instance.code.eq_ignore_ascii_case(code))
+            .map(|instance| &*instance.name)
+    }
+
+    /// Looks up the name of the source instance which provided the arrival time.
+    pub fn arrival_source_name(&self, time: &ServiceTime) -> Option<&str> {
+        time.arrival_source_instance
+            .as_deref()
+            .and_then(|instance| self.name(instance))
+    }
+
+    /// Looks up the name of the source instance which provided the departure time.
+    pub fn departure_source_name(&self, time: &ServiceTime) -> Option<&str> {
+        time.departure_source_instance
+            .as_deref()
+            .and_then(|instance| self.name(instance))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SourceInstanceList<'a> {
+    type Error = ParsingError<'a>;
+
+    fn try_from(string: &'a str) -> Result<SourceInstanceList<'a>, ParsingError<'a>> {
+        let document =
+            Document::parse(string).map_err(|e| ParsingError::XMLParseError { source: e })?;
+
+        let result = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("GetSourceInstanceNamesResult"))
+            .ok_or_else(|| missing_result(&document, "GetSourceInstanceNamesResult"))?;
+
+        let mut instances = Vec::new();
+
+        for node in result.children().filter(|x| x.is_element()) {
+            instances.push(SourceInstance::parse_located(&node, string)?)
+        }
+
+        Ok(SourceInstanceList { instances })
+    }
+}
+
+#[cfg(feature = "pretty")]
+impl<'a> PrettyPrintable for SourceInstanceList<'a> {
+    fn pretty(&self) -> String {
+        self.instances
+            .iter()
+            .map(|instance| {
+                ANSIStrings(&[
+                    Fixed(PURPLE).bold().paint(format!("{:<8}", instance.code)),
+                    Style::default().paint(&*instance.name),
+                    Style::default().paint("\n"),
+                ])
+                .to_string()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+
+/// The start of the last Sunday of `month` in `year`, at 01:00 UTC. British Summer Time starts and ends at these
+/// times in March and October.
+fn last_sunday(year: i32, month: u32) -> NaiveDateTime {
+    let last = NaiveDate::from_ymd_opt(year, month, 31).expect("March and October have 31 days");
+
+    (last - Duration::days(last.weekday().num_days_from_sunday().into()))
+        .and_hms_opt(1, 0, 0)
+        .expect("01:00 is a valid time")
+}
+
+/// Converts a time to UK time, which is what the API expects times to be in. The UK is on GMT in winter, and on
+/// British Summer Time (an hour ahead) from the last Sunday in March until the last Sunday in October.
+pub fn uk_time<Tz: TimeZone>(time: &DateTime<Tz>) -> DateTime<FixedOffset> {
+    let utc = time.naive_utc();
+    let summer = (last_sunday(utc.year(), 3)..last_sunday(utc.year(), 10)).contains(&utc);
+    let offset = FixedOffset::east_opt(if summer { 3600 } else { 0 }).expect("offset is in range");
+
+    time.with_timezone(&offset)
+}
@@ -0,0 +1,123 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use roxmltree::Node;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The address of the OpenLDBSVWS endpoints, without the file for a version.
+pub const ENDPOINT_BASE: &str = "https://lite.realtime.nationalrail.co.uk/OpenLDBSVWS/";
+
+/// A version of the OpenLDBSVWS schema. Each version has its own endpoint and request namespace, and newer versions
+/// add elements to responses.
+///
+/// Responses are parsed by local name, so the parsers accept responses of either version. Pinning a version makes it
+/// possible to keep using an old schema during a migration, or to compare the output of two versions.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    /// The 2017-10-01 schema, served by `ldbsv12.asmx`.
+    V12,
+    /// The 2021-11-01 schema, served by `ldbsv13.asmx`. This is the default.
+    #[default]
+    V13,
+}
+
+impl ApiVersion {
+    /// Every version, oldest first.
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V12, ApiVersion::V13];
+
+    /// The date that identifies this version in namespaces.
+    fn date(&self) -> &'static str {
+        match self {
+            ApiVersion::V12 => "2017-10-01",
+            ApiVersion::V13 => "2021-11-01",
+        }
+    }
+
+    /// The file of this version's endpoint, such as "ldbsv13.asmx".
+    pub fn path(&self) -> &'static str {
+        match self {
+            ApiVersion::V12 => "ldbsv12.asmx",
+            ApiVersion::V13 => "ldbsv13.asmx",
+        }
+    }
+
+    /// The default endpoint of this version.
+    pub fn endpoint(&self) -> String {
+        format!("{}{}", ENDPOINT_BASE, self.path())
+    }
+
+    /// The namespace of requests to this version.
+    pub fn namespace(&self) -> &'static str {
+        match self {
+            ApiVersion::V12 => "http://thalesgroup.com/RTTI/2017-10-01/ldbsv/",
+            ApiVersion::V13 => "http://thalesgroup.com/RTTI/2021-11-01/ldbsv/",
+        }
+    }
+
+    /// Finds the version a namespace belongs to. Both the request namespace and the types namespaces of a version are
+    /// recognised, such as "http://thalesgroup.com/RTTI/2021-11-01/ldbsv/types".
+    pub fn from_namespace(namespace: &str) -> Option<ApiVersion> {
+        let date = namespace
+            .strip_prefix("http://thalesgroup.com/RTTI/")?
+            .split('/')
+            .next()?;
+
+        ApiVersion::ALL
+            .into_iter()
+            .find(|version| version.date() == date)
+    }
+
+    /// Finds the version of the response a node is in, from the namespaces in scope. Responses mix the namespaces of
+    /// every version an element was introduced in, so this is the newest version found. If None, no namespace of a
+    /// known version is in scope.
+    pub(crate) fn detect(node: &Node) -> Option<ApiVersion> {
+        let mut version = None;
+
+        for namespace in node.namespaces() {
+            version = version.max(ApiVersion::from_namespace(namespace.uri()));
+        }
+
+        version
+    }
+
+    /// If true, responses of this version include the formation of trains, with the loading of the whole train and of
+    /// each coach. Earlier versions don't, so their locations never have a formation.
+    pub fn has_formations(&self) -> bool {
+        *self >= ApiVersion::V13
+    }
+}
+
+impl Display for ApiVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiVersion::V12 => write!(f, "12"),
+            ApiVersion::V13 => write!(f, "13"),
+        }
+    }
+}
+
+/// An unknown API version was given.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown API version {0}, expected 12 or 13")]
+pub struct UnknownApiVersion(pub String);
+
+impl FromStr for ApiVersion {
+    type Err = UnknownApiVersion;
+
+    /// Parses a version number, such as "13", optionally prefixed with "v" or "ldbsv".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = s
+            .strip_prefix("ldbsv")
+            .or_else(|| s.strip_prefix('v'))
+            .unwrap_or(s);
+
+        match number {
+            "12" => Ok(ApiVersion::V12),
+            "13" => Ok(ApiVersion::V13),
+            _ => Err(UnknownApiVersion(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use openldbsvws_lib::{Activities, Activity, UnknownActivity};
+
+#[test]
+fn every_code_round_trips() {
+    for activity in Activity::ALL {
+        let code = activity.to_string();
+
+        assert_eq!(code.len(), 2, "{:?}", activity);
+        assert_eq!(code.parse::<Activity>(), Ok(activity));
+        assert_eq!(activity.code().parse::<Activity>(), Ok(activity));
+    }
+}
+
+#[test]
+fn single_character_codes_are_padded() {
+    assert_eq!(
+        Activity::StopsToTakeUpAndSetDownPassengers.to_string(),
+        "T "
+    );
+    assert_eq!(Activity::StopDetach.to_string(), "-D");
+    assert_eq!(Activity::None.to_string(), "  ");
+    assert_eq!(format!("[{:4}]", Activity::TrainBegins), "[TB  ]");
+}
+
+#[test]
+fn unknown_codes_are_rejected() {
+    assert_eq!(
+        "ZZ".parse::<Activity>(),
+        Err(UnknownActivity("ZZ".to_string()))
+    );
+    assert!("TBT".parse::<Activity>().is_err());
+}
+
+#[test]
+fn cif_field() {
+    let activities: Activities = "T -D        ".parse().unwrap();
+
+    assert_eq!(
+        &*activities,
+        [
+            Activity::StopsToTakeUpAndSetDownPassengers,
+            Activity::StopDetach
+        ]
+    );
+    assert_eq!(activities.to_string(), "T -D");
+    assert_eq!(format!("{:12}|", activities), "T -D        |");
+
+    // Darwin trims trailing spaces, which can leave half a code at the end.
+    let activities: Activities = "TBR".parse().unwrap();
+
+    assert_eq!(
+        &*activities,
+        [Activity::TrainBegins, Activity::StopsWhenRequired]
+    );
+
+    // Blank codes aren't activities.
+    assert!("            ".parse::<Activities>().unwrap().is_empty());
+    assert!("".parse::<Activities>().unwrap().is_empty());
+}
+
+#[test]
+fn cif_field_with_unknown_code() {
+    assert_eq!(
+        "T ZZ".parse::<Activities>(),
+        Err(UnknownActivity("ZZ".to_string()))
+    );
+
+    // Codes are two bytes, so anything other than ASCII fails rather than splitting a character.
+    assert!("Tü".parse::<Activities>().is_err());
+    assert!("é".parse::<Activities>().is_err());
+}
+
+#[test]
+fn passenger_helpers() {
+    let stop: Activities = "T ".parse().unwrap();
+
+    assert!(stop.is_public_stop() && stop.picks_up() && stop.sets_down());
+    assert!(!stop.is_request_stop());
+
+    let pick_up: Activities = "U ".parse().unwrap();
+
+    assert!(pick_up.is_public_stop() && pick_up.picks_up() && !pick_up.sets_down());
+
+    let set_down: Activities = "D ".parse().unwrap();
+
+    assert!(set_down.is_public_stop() && !set_down.picks_up() && set_down.sets_down());
+
+    let request: Activities = "T R ".parse().unwrap();
+
+    assert!(request.is_public_stop() && request.is_request_stop());
+
+    let unadvertised: Activities = "N ".parse().unwrap();
+
+    assert!(!unadvertised.is_public_stop());
+
+    let operational: Activities = "OPRM".parse().unwrap();
+
+    assert!(!operational.is_public_stop() && !operational.picks_up() && !operational.sets_down());
+}
+
+#[test]
+fn descriptions() {
+    assert_eq!(Activity::TrainBegins.description(), "Train begins");
+
+    for activity in Activity::ALL {
+        assert!(!activity.description().is_empty());
+    }
+}
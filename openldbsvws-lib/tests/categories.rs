@@ -0,0 +1,44 @@
+use openldbsvws_lib::{ServiceType, TrainCategory, UnknownServiceType};
+
+#[test]
+fn category_codes_round_trip() {
+    for code in ["OO", "XX", "BR", "SS", "EE", "ZZ", "J2", "B7", "H6"] {
+        let category = TrainCategory::from_code(code);
+
+        assert!(!matches!(category, TrainCategory::Unknown(_)), "{}", code);
+        assert_eq!(category.code(), code);
+        assert_eq!(category.to_string(), code);
+    }
+}
+
+#[test]
+fn unknown_categories_keep_their_code() {
+    let category = TrainCategory::from("Q1");
+
+    assert_eq!(category, TrainCategory::Unknown("Q1".to_string()));
+    assert_eq!(category.code(), "Q1");
+    assert_eq!(category.description(), "unknown");
+}
+
+#[test]
+fn category_groups() {
+    assert!(TrainCategory::ExpressPassenger.is_passenger());
+    assert!(TrainCategory::ReplacementBus.is_passenger() && TrainCategory::ReplacementBus.is_bus());
+    assert!(!TrainCategory::EmptyCoachingStock.is_passenger());
+    assert!(!TrainCategory::EmptyCoachingStock.is_freight());
+    assert!(TrainCategory::Aggregates.is_freight() && !TrainCategory::Aggregates.is_bus());
+    assert!(TrainCategory::ChannelTunnelIntermodal.is_freight());
+    assert!(!TrainCategory::ChannelTunnel.is_freight());
+}
+
+#[test]
+fn service_types() {
+    for typ in [ServiceType::Train, ServiceType::Bus, ServiceType::Ferry] {
+        assert_eq!(typ.to_string().parse::<ServiceType>(), Ok(typ));
+    }
+
+    assert_eq!(
+        "hovercraft".parse::<ServiceType>(),
+        Err(UnknownServiceType("hovercraft".to_string()))
+    );
+}
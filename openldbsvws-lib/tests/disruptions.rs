@@ -0,0 +1,74 @@
+use openldbsvws_lib::{DisruptionList, ParsingError};
+
+fn response(messages: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><GetDisruptionListResponse xmlns="http://thalesgroup.com/RTTI/2021-11-01/ldbsv/"><GetDisruptionListResult>{}</GetDisruptionListResult></GetDisruptionListResponse></soap:Body></soap:Envelope>"#,
+        messages
+    )
+}
+
+fn message(body: &str, stations: &str) -> String {
+    format!(
+        "<message><id>1</id><category>Station</category><severity>1</severity><xhtmlMessage>{}</xhtmlMessage><stations>{}</stations></message>",
+        body, stations
+    )
+}
+
+#[test]
+fn escaped_messages_are_decoded_once() {
+    let string = response(&message(
+        "&lt;p&gt;Fish &amp;amp; chips &amp;lt;b&amp;gt; &lt;a href=\"x\"&gt;here&lt;/a&gt;&lt;/p&gt;",
+        "<crs>KGX</crs>",
+    ));
+    let list = DisruptionList::try_from(string.as_str()).unwrap();
+    let message = &list.messages[0];
+
+    assert_eq!(
+        message.message,
+        "<p>Fish &amp; chips &lt;b&gt; <a href=\"x\">here</a></p>"
+    );
+    // The escaped tag is text, so it must survive having the real tags stripped.
+    assert_eq!(message.plain_message(), "Fish & chips <b> here");
+    assert_eq!(message.stations, ["KGX"]);
+}
+
+#[test]
+fn inline_messages_are_kept_as_html() {
+    let string = response(&message(
+        "<p>Fish &amp; chips <a href=\"x\">here</a></p>",
+        "",
+    ));
+    let list = DisruptionList::try_from(string.as_str()).unwrap();
+    let message = &list.messages[0];
+
+    assert_eq!(
+        message.message,
+        "<p>Fish &amp; chips <a href=\"x\">here</a></p>"
+    );
+    assert_eq!(message.plain_message(), "Fish & chips here");
+    assert!(message.stations.is_empty());
+}
+
+#[test]
+fn invalid_stations_fail() {
+    let string = response(&message("Closed", "<crs>kgx</crs>"));
+
+    match DisruptionList::try_from(string.as_str()) {
+        Err(ParsingError::At { error, .. }) => {
+            assert!(matches!(
+                *error,
+                ParsingError::InvalidField { field: "crs", .. }
+            ))
+        }
+        result => panic!("expected an invalid CRS, got {:?}", result),
+    }
+}
+
+#[test]
+fn invalid_stations_are_left_out_when_lenient() {
+    let string = response(&message("Closed", "<crs>kgx</crs><crs>EUS</crs>"));
+    let list = DisruptionList::parse_lenient(&string).unwrap();
+
+    assert_eq!(list.messages[0].stations, ["EUS"]);
+    assert_eq!(list.warnings.len(), 1);
+}
@@ -0,0 +1,119 @@
+use openldbsvws_lib::{ApiError, Fault};
+
+/// A SOAP 1.1 fault.
+fn fault(code: &str, message: &str, detail: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><soap:Fault><faultcode>{}</faultcode><faultstring>{}</faultstring><detail>{}</detail></soap:Fault></soap:Body></soap:Envelope>"#,
+        code, message, detail
+    )
+}
+
+/// A SOAP 1.2 fault.
+fn fault12(code: &str, message: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope"><soap:Body><soap:Fault><soap:Code><soap:Value>{}</soap:Value></soap:Code><soap:Reason><soap:Text xml:lang="en">{}</soap:Text></soap:Reason></soap:Fault></soap:Body></soap:Envelope>"#,
+        code, message
+    )
+}
+
+#[test]
+fn token() {
+    let body = fault("soap:Client", "Unauthorised", "");
+
+    assert!(matches!(
+        ApiError::from_response(401, &body, false),
+        Some(ApiError::InvalidToken(_))
+    ));
+
+    // Without a fault, the status is used as the fault.
+    match ApiError::from_response(403, "", true) {
+        Some(ApiError::InvalidToken(fault)) => assert_eq!(
+            fault,
+            Fault {
+                code: "403".to_string(),
+                message: "Unauthorized".to_string(),
+                detail: None,
+            }
+        ),
+        error => panic!("expected an invalid token, got {:?}", error),
+    }
+}
+
+#[test]
+fn rate_limited() {
+    assert!(matches!(
+        ApiError::from_response(429, "", false),
+        Some(ApiError::RateLimited(_))
+    ));
+}
+
+#[test]
+fn descriptions_are_not_used() {
+    // Mentioning a token or something not being found doesn't make a fault about either.
+    let body = fault("soap:Client", "Invalid CRS", "The token value was valid");
+
+    assert!(matches!(
+        ApiError::from_response(500, &body, false),
+        Some(ApiError::InvalidRequest(_))
+    ));
+
+    let body = fault("soap:Client", "Station not found", "");
+
+    assert!(matches!(
+        ApiError::from_response(500, &body, false),
+        Some(ApiError::InvalidRequest(_))
+    ));
+
+    let body = fault("soap:Server", "Service not found", "");
+
+    assert!(matches!(
+        ApiError::from_response(500, &body, true),
+        Some(ApiError::ServerError(_))
+    ));
+}
+
+#[test]
+fn service_not_found() {
+    let body = fault("soap:Client", "Invalid RID", "");
+
+    assert!(matches!(
+        ApiError::from_response(500, &body, true),
+        Some(ApiError::ServiceNotFound(_))
+    ));
+    assert!(matches!(
+        ApiError::from_response(404, "", true),
+        Some(ApiError::ServiceNotFound(_))
+    ));
+
+    // A 404 without a fault for anything else says nothing about what went wrong.
+    assert_eq!(ApiError::from_response(404, "", false), None);
+}
+
+#[test]
+fn soap_12() {
+    assert!(matches!(
+        ApiError::from_response(500, &fault12("soap:Sender", "Invalid CRS"), false),
+        Some(ApiError::InvalidRequest(_))
+    ));
+    assert!(matches!(
+        ApiError::from_response(500, &fault12("soap:Receiver", "Oops"), true),
+        Some(ApiError::ServerError(_))
+    ));
+}
+
+#[test]
+fn without_status() {
+    // Faults in successful responses only have their code to go on.
+    let fault = Fault {
+        code: "soap:Client".to_string(),
+        message: "Unauthorised: the access token is not valid".to_string(),
+        detail: None,
+    };
+
+    assert!(matches!(ApiError::from(fault), ApiError::InvalidRequest(_)));
+}
+
+#[test]
+fn unknown() {
+    assert_eq!(ApiError::from_response(502, "Bad Gateway", false), None);
+}
@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+
+use openldbsvws_lib::{Coach, Formation, TrainEnd};
+
+/// A formation with a coach for each loading, lettered from the front.
+fn formation(loadings: &[Option<u8>]) -> Formation<'static> {
+    Formation {
+        coaches: loadings
+            .iter()
+            .zip('A'..)
+            .map(|(&loading, number)| Coach {
+                number: Cow::Owned(number.to_string()),
+                class: None,
+                toilet: None,
+                toilet_status: None,
+                loading,
+            })
+            .collect(),
+        loading: None,
+    }
+}
+
+#[test]
+fn threshold() {
+    // 15 points between the ends is enough, 14 isn't.
+    assert_eq!(
+        formation(&[Some(20), Some(50), Some(35)]).quieter_end(),
+        Some(TrainEnd::Front)
+    );
+    assert_eq!(
+        formation(&[Some(35), Some(50), Some(20)]).quieter_end(),
+        Some(TrainEnd::Rear)
+    );
+    assert_eq!(
+        formation(&[Some(21), Some(50), Some(35)]).quieter_end(),
+        None
+    );
+    assert_eq!(
+        formation(&[Some(35), Some(50), Some(21)]).quieter_end(),
+        None
+    );
+}
+
+#[test]
+fn averages_each_half() {
+    assert_eq!(
+        formation(&[Some(10), Some(30), Some(40), Some(40)]).quieter_end(),
+        Some(TrainEnd::Front)
+    );
+}
+
+#[test]
+fn single_coach() {
+    assert_eq!(formation(&[Some(10)]).quieter_end(), None);
+    assert_eq!(formation(&[]).quieter_end(), None);
+}
+
+#[test]
+fn coaches_without_loading() {
+    // Coaches with unknown loading are left out of the average.
+    assert_eq!(
+        formation(&[None, Some(10), Some(50), Some(60)]).quieter_end(),
+        Some(TrainEnd::Front)
+    );
+    // If either end has no loading data, neither end can be called quieter.
+    assert_eq!(
+        formation(&[None, None, Some(50), Some(60)]).quieter_end(),
+        None
+    );
+    assert_eq!(formation(&[None, None, None]).quieter_end(), None);
+}
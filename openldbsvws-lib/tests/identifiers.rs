@@ -0,0 +1,185 @@
+use chrono::NaiveDate;
+use openldbsvws_lib::{
+    Crs, DeparturesBoard, Headcode, InvalidIdentifier, Rid, Rsid, ServiceDetails, ServiceList,
+    StationBoard, Tiploc, Uid,
+};
+
+#[test]
+fn rid() {
+    let rid: Rid = "202209207612345".parse().unwrap();
+
+    assert_eq!(rid.date(), NaiveDate::from_ymd_opt(2022, 9, 20));
+    assert_eq!(rid.serial(), "7612345");
+    assert_eq!(rid, "202209207612345");
+
+    // Too short, not digits, and not starting with a date.
+    assert!("20220920761234".parse::<Rid>().is_err());
+    assert!("20220920761234A".parse::<Rid>().is_err());
+    assert!("202213407612345".parse::<Rid>().is_err());
+}
+
+#[test]
+fn headcode() {
+    let headcode: Headcode = "1A23".parse().unwrap();
+
+    assert_eq!(headcode.class(), Some(1));
+    assert_eq!(headcode.destination_area(), Some('A'));
+    assert_eq!(headcode.serial(), "23");
+
+    assert!("0B00".parse::<Headcode>().is_ok());
+    assert!("A123".parse::<Headcode>().is_err());
+    assert!("1a23".parse::<Headcode>().is_err());
+    assert!("1A234".parse::<Headcode>().is_err());
+}
+
+#[test]
+fn rsid() {
+    let rsid: Rsid = "GW123400".parse().unwrap();
+
+    assert_eq!(rsid.toc(), "GW");
+    assert_eq!(rsid.service_number(), "1234");
+    assert_eq!(rsid.portion(), Some("00"));
+
+    let rsid: Rsid = "GW1234".parse().unwrap();
+
+    assert_eq!(rsid.portion(), None);
+    assert!("GW12345".parse::<Rsid>().is_err());
+    assert!("G1234500".parse::<Rsid>().is_err());
+}
+
+#[test]
+fn locations() {
+    assert!("KGX".parse::<Crs>().is_ok());
+    assert!("KG".parse::<Crs>().is_err());
+    assert!("kgx".parse::<Crs>().is_err());
+
+    assert!("KNGX".parse::<Tiploc>().is_ok());
+    assert!("CLPHMJ2".parse::<Tiploc>().is_ok());
+    assert!("".parse::<Tiploc>().is_err());
+    assert!("KINGSCROSS".parse::<Tiploc>().is_err());
+}
+
+#[test]
+fn uid() {
+    assert!("P12345".parse::<Uid>().is_ok());
+    assert!("123456".parse::<Uid>().is_err());
+    assert!("P1234".parse::<Uid>().is_err());
+}
+
+#[test]
+fn errors_say_what_was_expected() {
+    assert_eq!(
+        "KG".parse::<Crs>(),
+        Err(InvalidIdentifier {
+            expected: "CRS code of three letters",
+            found: "KG".to_string(),
+        })
+    );
+}
+
+#[test]
+fn borrowed_identifiers_can_be_owned() {
+    let string = String::from("KNGX");
+    let tiploc = Tiploc::new(&string).unwrap().into_owned();
+
+    drop(string);
+
+    assert_eq!(tiploc.as_str(), "KNGX");
+    assert_eq!(tiploc.to_string(), "KNGX");
+}
+
+#[test]
+fn invalid_identifiers_in_responses_fail() {
+    let response = include_str!("fixtures/responses/service_details.xml").replace(
+        "<t:trainid>1A23</t:trainid>",
+        "<t:trainid>nonsense</t:trainid>",
+    );
+
+    let error = ServiceDetails::try_from(response.as_str()).unwrap_err();
+
+    assert_eq!(
+        error.location().map(|location| location.path.as_str()),
+        Some("GetServiceDetailsResult/trainid")
+    );
+}
+
+#[test]
+fn invalid_identifiers_are_kept_when_lenient() {
+    let response = include_str!("fixtures/responses/service_details.xml").replace(
+        "<t:trainid>1A23</t:trainid>",
+        "<t:trainid>nonsense</t:trainid>",
+    );
+
+    let details = ServiceDetails::parse_lenient(&response).unwrap();
+
+    assert_eq!(details.trainid, "nonsense");
+    assert_eq!(details.trainid.class(), None);
+    assert_eq!(details.warnings.len(), 1);
+    assert_eq!(
+        details.warnings[0]
+            .location
+            .as_ref()
+            .map(|x| x.path.as_str()),
+        Some("GetServiceDetailsResult/trainid")
+    );
+
+    let response = include_str!("fixtures/responses/board_departures.xml")
+        .replace("<t:rid>202209207612345</t:rid>", "<t:rid>nonsense</t:rid>");
+    let board = StationBoard::parse_lenient(&response).unwrap();
+
+    assert_eq!(board.train_services[0].rid, "nonsense");
+    assert_eq!(board.train_services[0].rid.date(), None);
+    assert_eq!(board.warnings.len(), 1);
+}
+
+#[test]
+fn invalid_optional_identifiers_are_left_out_when_lenient() {
+    let response = include_str!("fixtures/responses/query_services.xml").replace(
+        "<t:originCRS>BRI</t:originCRS>",
+        "<t:originCRS>Bristol</t:originCRS>",
+    );
+
+    assert!(ServiceList::try_from(response.as_str()).is_err());
+
+    let list = ServiceList::parse_lenient(&response).unwrap();
+    let origin = list.services[0].origin.as_ref().unwrap();
+
+    assert!(origin.crs.is_none());
+    assert_eq!(origin.tiploc.as_deref(), Some("BRSTLTM"));
+    assert_eq!(list.warnings.len(), 1);
+
+    // Missing identifiers are just missing.
+    let response = include_str!("fixtures/responses/query_services.xml")
+        .replace("<t:originCRS>BRI</t:originCRS>", "");
+    let list = ServiceList::try_from(response.as_str()).unwrap();
+
+    assert!(list.services[0].origin.as_ref().unwrap().crs.is_none());
+}
+
+#[test]
+fn board_services_have_identifiers() {
+    let board =
+        StationBoard::try_from(include_str!("fixtures/responses/board_departures.xml")).unwrap();
+    let service = &board.train_services[0];
+
+    assert_eq!(service.rid.date(), NaiveDate::from_ymd_opt(2022, 9, 20));
+    assert_eq!(service.uid, "P12345");
+    assert_eq!(service.trainid, "1A23");
+    assert!(service.rsid.is_none());
+
+    let response = include_str!("fixtures/responses/board_departures.xml")
+        .replace("<t:rid>202209207612345</t:rid>", "<t:rid>nonsense</t:rid>");
+
+    assert!(StationBoard::try_from(response.as_str()).is_err());
+}
+
+#[test]
+fn departures_are_keyed_by_crs() {
+    let board =
+        DeparturesBoard::try_from(include_str!("fixtures/responses/departures_next.xml")).unwrap();
+    let pad: Crs = "PAD".parse().unwrap();
+
+    assert_eq!(board.departures[&pad].crs, "PAD");
+    assert!(board.departures[&pad].service.is_some());
+    assert!(board.departures.keys().eq(["OXF", "PAD"].iter()));
+}
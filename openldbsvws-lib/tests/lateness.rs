@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, FixedOffset};
+use openldbsvws_lib::{
+    ForecastType, Lateness, LatenessTolerance, ServiceDetails, ServiceTime, UserLateness,
+};
+
+fn at(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(&format!("2022-10-04T{}+01:00", time)).unwrap()
+}
+
+/// The times of a location the service departs from at 08:00.
+fn departure(typ: Option<ForecastType>, departure: Option<&str>) -> ServiceTime<'static> {
+    ServiceTime {
+        scheduled_arrival: None,
+        scheduled_departure: Some(at("08:00:00")),
+        working_arrival: None,
+        working_departure: Some(at("08:00:00")),
+        working_pass: None,
+        arrival: None,
+        departure: departure.map(at),
+        arrival_forecast_type: None,
+        departure_forecast_type: typ,
+        arrival_source: None,
+        arrival_source_instance: None,
+        departure_source: None,
+        departure_source_instance: None,
+    }
+}
+
+/// Checks that `tolerance` allows up to `late` and, if any, `early`, but not a second more.
+fn assert_boundaries(tolerance: LatenessTolerance, late: Duration, early: Option<Duration>) {
+    let second = Duration::seconds(1);
+
+    assert_eq!(tolerance.classify(late), UserLateness::OnTime(late));
+    assert_eq!(
+        tolerance.classify(late + second),
+        UserLateness::Late(late + second)
+    );
+
+    match early {
+        Some(early) => {
+            assert_eq!(tolerance.classify(-early), UserLateness::OnTime(-early));
+            assert_eq!(
+                tolerance.classify(-early - second),
+                UserLateness::Early(-early - second)
+            );
+        }
+        None => {
+            let hour = Duration::hours(-1);
+
+            assert_eq!(tolerance.classify(hour), UserLateness::OnTime(hour));
+        }
+    }
+}
+
+#[test]
+fn minute() {
+    assert_boundaries(
+        LatenessTolerance::minute(),
+        Duration::minutes(1),
+        Some(Duration::minutes(1)),
+    );
+    assert_eq!(LatenessTolerance::default(), LatenessTolerance::minute());
+}
+
+#[test]
+fn right_time() {
+    assert_boundaries(LatenessTolerance::right_time(), Duration::seconds(59), None);
+}
+
+#[test]
+fn ppm() {
+    assert_boundaries(
+        LatenessTolerance::ppm_short_distance(),
+        Duration::minutes(5),
+        None,
+    );
+    assert_boundaries(
+        LatenessTolerance::ppm_long_distance(),
+        Duration::minutes(10),
+        None,
+    );
+}
+
+#[test]
+fn forecast_types() {
+    let tolerance = LatenessTolerance::ppm_short_distance();
+
+    assert_eq!(
+        departure(Some(ForecastType::Actual), Some("08:06:00")).lateness_with(&tolerance),
+        Some(UserLateness::Late(Duration::minutes(6)))
+    );
+    assert_eq!(
+        departure(Some(ForecastType::Estimated), Some("08:05:00")).lateness_with(&tolerance),
+        Some(UserLateness::OnTime(Duration::minutes(5)))
+    );
+    assert_eq!(
+        departure(Some(ForecastType::Delayed), None).lateness_with(&tolerance),
+        Some(UserLateness::Delayed)
+    );
+    assert_eq!(
+        departure(Some(ForecastType::NoLog), None).lateness_with(&tolerance),
+        None
+    );
+    assert_eq!(
+        departure(Some(ForecastType::NoReport), None).lateness_with(&tolerance),
+        None
+    );
+    assert_eq!(departure(None, None).lateness_with(&tolerance), None);
+}
+
+fn service(locations: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><GetServiceDetailsByRIDResponse xmlns="http://thalesgroup.com/RTTI/2021-11-01/ldbsv/"><GetServiceDetailsResult xmlns:t="http://thalesgroup.com/RTTI/2021-11-01/ldbsv/types"><t:generatedAt>2022-10-04T08:30:00+01:00</t:generatedAt><t:rid>202210047100005</t:rid><t:uid>C10005</t:uid><t:trainid>2T05</t:trainid><t:sdd>2022-10-04</t:sdd><t:operator>Elizabeth line</t:operator><t:operatorCode>XR</t:operatorCode><t:serviceType>train</t:serviceType><t:category>OO</t:category><t:locations>{}</t:locations></GetServiceDetailsResult></GetServiceDetailsByRIDResponse></soap:Body></soap:Envelope>"#,
+        locations
+    )
+}
+
+#[test]
+fn last_reported_location() {
+    let string = service(concat!(
+        // Departed 3 minutes late.
+        "<t:location><t:locationName>London Paddington</t:locationName><t:tiploc>PADTLL</t:tiploc>",
+        "<t:std>2022-10-04T08:00:00+01:00</t:std><t:atd>2022-10-04T08:03:00+01:00</t:atd>",
+        "<t:departureType>Actual</t:departureType></t:location>",
+        // Arrived 8 minutes late, but not departed yet.
+        "<t:location><t:locationName>Southall</t:locationName><t:tiploc>STHALL</t:tiploc>",
+        "<t:sta>2022-10-04T08:10:00+01:00</t:sta><t:ata>2022-10-04T08:18:00+01:00</t:ata>",
+        "<t:arrivalType>Actual</t:arrivalType><t:std>2022-10-04T08:11:00+01:00</t:std>",
+        "<t:etd>2022-10-04T08:19:00+01:00</t:etd><t:departureType>Forecast</t:departureType></t:location>",
+        // Cancelled here, so its report doesn't count.
+        "<t:location><t:locationName>Slough</t:locationName><t:tiploc>SLOUGH</t:tiploc>",
+        "<t:isCancelled>true</t:isCancelled><t:std>2022-10-04T08:20:00+01:00</t:std>",
+        "<t:atd>2022-10-04T08:40:00+01:00</t:atd><t:departureType>Actual</t:departureType></t:location>",
+        // Not reported yet.
+        "<t:location><t:locationName>Reading</t:locationName><t:tiploc>RDNGSTN</t:tiploc>",
+        "<t:sta>2022-10-04T08:40:00+01:00</t:sta><t:arrivalType>Delayed</t:arrivalType></t:location>",
+    ));
+    let details = ServiceDetails::try_from(string.as_str()).unwrap();
+
+    assert_eq!(
+        details.lateness(),
+        Some(UserLateness::Late(Duration::minutes(8)))
+    );
+    assert_eq!(
+        details.lateness_with(&LatenessTolerance::ppm_long_distance()),
+        Some(UserLateness::OnTime(Duration::minutes(8)))
+    );
+}
+
+#[test]
+fn not_reported_anywhere() {
+    let string = service(concat!(
+        "<t:location><t:locationName>London Paddington</t:locationName><t:tiploc>PADTLL</t:tiploc>",
+        "<t:isCancelled>true</t:isCancelled><t:std>2022-10-04T08:00:00+01:00</t:std>",
+        "<t:atd>2022-10-04T08:03:00+01:00</t:atd><t:departureType>Actual</t:departureType></t:location>",
+        "<t:location><t:locationName>Reading</t:locationName><t:tiploc>RDNGSTN</t:tiploc>",
+        "<t:sta>2022-10-04T08:40:00+01:00</t:sta><t:eta>2022-10-04T08:45:00+01:00</t:eta>",
+        "<t:arrivalType>Forecast</t:arrivalType></t:location>",
+    ));
+    let details = ServiceDetails::try_from(string.as_str()).unwrap();
+
+    assert_eq!(details.lateness(), None);
+}
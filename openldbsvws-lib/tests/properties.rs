@@ -0,0 +1,287 @@
+//! Property tests for the parsers. Services are generated as structurally valid XML, which has to parse to what was
+//! generated. The same services with arbitrary text in their fields, cut short, or parsed against the wrong string may
+//! fail to parse, but must never panic.
+
+use openldbsvws_lib::{escape, Parsable, ServiceDetails, ServiceLocation};
+use proptest::prelude::*;
+use roxmltree::Document;
+
+/// Activities which are valid at a calling point, padded to two characters as in the CIF.
+const ACTIVITIES: [&str; 10] = ["T ", "D ", "U ", "R ", "N ", "-D", "-U", "OP", "RM", "A "];
+
+/// A generated location. Every field is text, so that it can hold anything.
+#[derive(Debug, Clone)]
+struct Stop {
+    name: String,
+    tiploc: String,
+    crs: Option<String>,
+    activities: String,
+    pass: String,
+    cancelled: String,
+    platform: Option<String>,
+    arrival: Option<String>,
+    departure: Option<String>,
+}
+
+/// A generated service.
+#[derive(Debug, Clone)]
+struct Service {
+    generated_at: String,
+    rid: String,
+    uid: String,
+    trainid: String,
+    sdd: String,
+    operator: String,
+    operator_code: String,
+    category: String,
+    stops: Vec<Stop>,
+}
+
+/// Writes an element, if it has a value.
+fn element(xml: &mut String, name: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        xml.push_str(&format!("<t:{0}>{1}</t:{0}>", name, escape(value)));
+    }
+}
+
+/// Writes a service as a `GetServiceDetailsByRID` response.
+fn service_xml(service: &Service) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?><soap:Envelope \
+         xmlns:soap=\"http://schemas.xmlsoap.org/soap/envelope/\"><soap:Body><GetServiceDetailsByRIDResponse \
+         xmlns=\"http://thalesgroup.com/RTTI/2021-11-01/ldbsv/\"><GetServiceDetailsResult \
+         xmlns:t=\"http://thalesgroup.com/RTTI/2021-11-01/ldbsv/types\">",
+    );
+
+    element(&mut xml, "generatedAt", Some(&service.generated_at));
+    element(&mut xml, "rid", Some(&service.rid));
+    element(&mut xml, "uid", Some(&service.uid));
+    element(&mut xml, "trainid", Some(&service.trainid));
+    element(&mut xml, "sdd", Some(&service.sdd));
+    element(&mut xml, "operator", Some(&service.operator));
+    element(&mut xml, "operatorCode", Some(&service.operator_code));
+    element(&mut xml, "serviceType", Some("train"));
+    element(&mut xml, "category", Some(&service.category));
+    xml.push_str("<t:locations>");
+
+    for stop in &service.stops {
+        let pass = stop.pass == "true";
+
+        xml.push_str("<t:location>");
+        element(&mut xml, "locationName", Some(&stop.name));
+        element(&mut xml, "tiploc", Some(&stop.tiploc));
+        element(&mut xml, "crs", stop.crs.as_deref());
+        element(&mut xml, "activities", Some(&stop.activities));
+        element(&mut xml, "isPass", Some(&stop.pass));
+        element(&mut xml, "isCancelled", Some(&stop.cancelled));
+        element(&mut xml, "platform", stop.platform.as_deref());
+
+        if pass {
+            element(&mut xml, "wtp", stop.departure.as_deref());
+        } else {
+            element(&mut xml, "sta", stop.arrival.as_deref());
+            element(&mut xml, "wta", stop.arrival.as_deref());
+            element(&mut xml, "std", stop.departure.as_deref());
+            element(&mut xml, "wtd", stop.departure.as_deref());
+        }
+
+        xml.push_str("</t:location>");
+    }
+
+    xml.push_str(
+        "</t:locations></GetServiceDetailsResult></GetServiceDetailsByRIDResponse></soap:Body></soap:Envelope>",
+    );
+    xml
+}
+
+/// A time on the day of the service, given as minutes after midnight.
+fn time(minutes: u32) -> String {
+    format!(
+        "2022-09-20T{:02}:{:02}:00+01:00",
+        minutes / 60 % 24,
+        minutes % 60
+    )
+}
+
+prop_compose! {
+    fn valid_stop()(
+        name in "[A-Z][a-z]{2,12}( [A-Z][a-z]{2,12}){0,2}",
+        tiploc in "[A-Z]{4,7}",
+        crs in proptest::option::of("[A-Z]{3}"),
+        activities in proptest::collection::vec(proptest::sample::select(ACTIVITIES.to_vec()), 0..3),
+        pass in any::<bool>(),
+        cancelled in any::<bool>(),
+        platform in proptest::option::of(1u8..20),
+        minutes in 0u32..1380,
+        dwell in 0u32..10,
+    ) -> Stop {
+        Stop {
+            name,
+            tiploc,
+            crs,
+            activities: if pass { String::new() } else { activities.concat() },
+            pass: pass.to_string(),
+            cancelled: cancelled.to_string(),
+            platform: platform.map(|x| x.to_string()),
+            arrival: if pass { None } else { Some(time(minutes)) },
+            departure: Some(time(minutes + dwell)),
+        }
+    }
+}
+
+prop_compose! {
+    fn valid_service()(
+        rid in "20220920[0-9]{7}",
+        uid in "[A-Z][0-9]{5}",
+        trainid in "[0-9][A-Z][0-9]{2}",
+        operator in "[A-Z][a-z]{3,10}( [A-Z][a-z]{3,10}){0,2}",
+        operator_code in "[A-Z]{2}",
+        category in "(OO|XX|EE|OL)",
+        stops in proptest::collection::vec(valid_stop(), 1..12),
+    ) -> Service {
+        Service {
+            generated_at: "2022-09-20T12:00:00.123+01:00".to_string(),
+            rid,
+            uid,
+            trainid,
+            sdd: "2022-09-20".to_string(),
+            operator,
+            operator_code,
+            category,
+            stops,
+        }
+    }
+}
+
+/// Either `valid`, or any text at all.
+fn noisy(valid: String, noise: String, use_noise: bool) -> String {
+    if use_noise {
+        noise
+    } else {
+        valid
+    }
+}
+
+prop_compose! {
+    /// A valid service with arbitrary text in some of its fields.
+    fn noisy_service()(
+        service in valid_service(),
+        noise in proptest::collection::vec((any::<String>(), any::<bool>()), 16),
+    ) -> Service {
+        let mut noise = noise.into_iter().cycle();
+        let mut next = |valid: String| {
+            let (text, use_noise) = noise.next().expect("cycled");
+
+            noisy(valid, text, use_noise)
+        };
+
+        Service {
+            generated_at: next(service.generated_at),
+            rid: next(service.rid),
+            sdd: next(service.sdd),
+            stops: service
+                .stops
+                .into_iter()
+                .map(|stop| Stop {
+                    name: next(stop.name),
+                    activities: next(stop.activities),
+                    pass: next(stop.pass),
+                    cancelled: next(stop.cancelled),
+                    platform: stop.platform.map(&mut next),
+                    arrival: stop.arrival.map(&mut next),
+                    departure: stop.departure.map(&mut next),
+                    ..stop
+                })
+                .collect(),
+            ..service
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn valid_services_parse(service in valid_service()) {
+        let xml = service_xml(&service);
+        let details = ServiceDetails::try_from(xml.as_str()).map_err(|e| e.to_string());
+
+        prop_assert!(details.is_ok(), "{:?}", details);
+
+        let details = details.unwrap();
+
+        prop_assert_eq!(&*details.rid, service.rid.as_str());
+        prop_assert_eq!(&*details.uid, service.uid.as_str());
+        prop_assert_eq!(&*details.trainid, service.trainid.as_str());
+        prop_assert_eq!(&*details.operator, service.operator.as_str());
+        prop_assert_eq!(details.locations.len(), service.stops.len());
+
+        for (location, stop) in details.locations.iter().zip(&service.stops) {
+            prop_assert_eq!(&*location.location.name, stop.name.as_str());
+            prop_assert_eq!(location.location.tiploc.as_deref(), Some(stop.tiploc.as_str()));
+            prop_assert_eq!(location.location.crs.as_deref(), stop.crs.as_deref());
+            prop_assert_eq!(location.pass, stop.pass == "true");
+            prop_assert_eq!(location.cancelled, stop.cancelled == "true");
+            prop_assert_eq!(location.platform.map(|x| x.to_string()), stop.platform.clone());
+            prop_assert_eq!(
+                location.activities.as_ref().map(|x| x.len()),
+                Some(stop.activities.len() / 2).filter(|x| *x > 0)
+            );
+        }
+
+        let lenient = ServiceDetails::parse_lenient(&xml).map_err(|e| e.to_string());
+
+        prop_assert!(matches!(&lenient, Ok(details) if details.warnings.is_empty()), "{:?}", lenient);
+    }
+
+    #[test]
+    fn noisy_services_never_panic(service in noisy_service()) {
+        let xml = service_xml(&service);
+        let strict = ServiceDetails::try_from(xml.as_str());
+        let lenient = ServiceDetails::parse_lenient(&xml);
+
+        // Lenient parsing only ever fails where strict parsing does.
+        if strict.is_ok() {
+            prop_assert!(matches!(&lenient, Ok(details) if details.warnings.is_empty()));
+        }
+
+        // Errors describe themselves without panicking too.
+        if let Err(e) = strict {
+            let _ = e.to_string();
+        }
+
+        // Identifiers kept from a lenient parse may be invalid, so nothing may assume they aren't.
+        if let Ok(details) = lenient {
+            let _ = (details.rid.date(), details.rid.serial());
+            let _ = (details.trainid.class(), details.trainid.destination_area(), details.trainid.serial());
+            let _ = details.into_owned();
+        }
+    }
+
+    #[test]
+    fn truncated_services_never_panic(service in valid_service(), cut in 0.0..1.0f64) {
+        let xml = service_xml(&service);
+        let mut end = (xml.len() as f64 * cut) as usize;
+
+        while !xml.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        // roxmltree closes elements left open at the end, so a cut after the last required field still parses, but
+        // only to what was there.
+        if let Ok(details) = ServiceDetails::try_from(&xml[..end]) {
+            prop_assert!(details.locations.len() <= service.stops.len());
+        }
+
+        let _ = ServiceDetails::parse_lenient(&xml[..end]);
+    }
+
+    #[test]
+    fn mismatched_strings_never_panic(service in valid_service(), other in any::<String>()) {
+        let xml = service_xml(&service);
+        let document = Document::parse(&xml).unwrap();
+
+        for node in document.descendants().filter(|x| x.has_tag_name("location")) {
+            let _ = ServiceLocation::parse(&node, &other);
+            let _ = ServiceLocation::parse(&node, "");
+        }
+    }
+}
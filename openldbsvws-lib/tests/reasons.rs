@@ -0,0 +1,83 @@
+use std::borrow::Cow;
+
+use openldbsvws_lib::{Reason, ReasonCatalogue, ReasonCode};
+
+fn catalogue() -> ReasonCatalogue {
+    let mut catalogue = ReasonCatalogue::new();
+
+    catalogue.insert(&ReasonCode {
+        code: 104,
+        late_reason: Cow::from("This train has been delayed by a fault with the signalling system"),
+        cancellation_reason: Cow::from(
+            "This train has been cancelled because of a fault with the signalling system",
+        ),
+    });
+
+    catalogue
+}
+
+fn reason(code: u16, tiploc: Option<&str>, near: bool) -> Reason<'_> {
+    Reason {
+        code,
+        tiploc: tiploc.map(Cow::from),
+        near,
+        text: None,
+    }
+}
+
+#[test]
+fn resolves_without_location() {
+    let catalogue = catalogue();
+    let reason = reason(104, None, false);
+
+    assert_eq!(
+        catalogue.resolve_late(&reason, None).unwrap(),
+        "This train has been delayed by a fault with the signalling system"
+    );
+    assert_eq!(
+        catalogue.resolve_cancellation(&reason, None).unwrap(),
+        "This train has been cancelled because of a fault with the signalling system"
+    );
+}
+
+#[test]
+fn resolves_at_and_near() {
+    let catalogue = catalogue();
+
+    assert_eq!(
+        catalogue
+            .resolve_late(&reason(104, Some("STEVNGE"), false), Some("Stevenage"))
+            .unwrap(),
+        "This train has been delayed by a fault with the signalling system at Stevenage"
+    );
+    assert_eq!(
+        catalogue
+            .resolve_cancellation(&reason(104, Some("STEVNGE"), true), Some("Stevenage"))
+            .unwrap(),
+        "This train has been cancelled because of a fault with the signalling system near Stevenage"
+    );
+}
+
+#[test]
+fn falls_back_to_tiploc() {
+    let catalogue = catalogue();
+
+    assert_eq!(
+        catalogue
+            .resolve_late(&reason(104, Some("STEVNGE"), true), None)
+            .unwrap(),
+        "This train has been delayed by a fault with the signalling system near STEVNGE"
+    );
+}
+
+#[test]
+fn unknown_codes() {
+    let catalogue = catalogue();
+    let reason = reason(999, Some("STEVNGE"), true);
+
+    assert!(catalogue.late_reason(999).is_none());
+    assert!(catalogue.resolve_late(&reason, Some("Stevenage")).is_none());
+    assert!(catalogue
+        .resolve_cancellation(&reason, Some("Stevenage"))
+        .is_none());
+}
@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+
+use openldbsvws_lib::{ReferenceData, StationList, TocList};
+
+const TOCS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetTOCListResponse xmlns="http://thalesgroup.com/RTTI/2015-05-14/ldbsv_ref/">
+      <GetTOCListResult>
+        <TOCList version="14">
+          <TOC toc="GN" Value="Great Northern"/>
+          <TOC toc="GW" Value="Great Western Railway"/>
+        </TOCList>
+      </GetTOCListResult>
+    </GetTOCListResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+
+const STATIONS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetStationListResponse xmlns="http://thalesgroup.com/RTTI/2015-05-14/ldbsv_ref/">
+      <GetStationListResult>
+        <StationList version="93">
+          <Station crs="HIT" Value="Hitchin"/>
+          <Station crs="HXX" Value="Heathrow Terminals 2 &amp; 3"/>
+          <Station crs="PBO" Value="Peterborough"/>
+        </StationList>
+      </GetStationListResult>
+    </GetStationListResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+
+#[test]
+fn entities_are_decoded() {
+    let list = StationList::try_from(STATIONS).unwrap();
+
+    assert_eq!(list.stations[1].name, "Heathrow Terminals 2 & 3");
+    assert!(matches!(list.stations[1].name, Cow::Owned(_)));
+
+    // Names without entities are still borrowed from the response.
+    assert!(matches!(list.stations[0].name, Cow::Borrowed("Hitchin")));
+}
+
+#[test]
+fn lookups() {
+    let mut reference = ReferenceData::new();
+
+    reference.update_tocs(&TocList::try_from(TOCS).unwrap());
+    reference.update_stations(&StationList::try_from(STATIONS).unwrap());
+
+    assert_eq!(reference.toc_name("gw"), Some("Great Western Railway"));
+    assert_eq!(
+        reference.station_name("HXX"),
+        Some("Heathrow Terminals 2 & 3")
+    );
+    assert!(reference.is_valid_crs("hit"));
+    assert!(!reference.is_valid_crs("KGX"));
+    assert_eq!(reference.station_version(), Some("93"));
+}
+
+#[test]
+fn unchanged_lists_are_kept() {
+    let mut reference = ReferenceData::new();
+
+    reference.update_stations(&StationList::try_from(STATIONS).unwrap());
+    reference.update_stations(&StationList {
+        version: "93".into(),
+        stations: Vec::new(),
+    });
+
+    assert_eq!(reference.stations().count(), 3);
+}
+
+#[test]
+fn stations_can_be_owned() {
+    let string = String::from(STATIONS);
+    let stations: Vec<_> = StationList::try_from(string.as_str())
+        .unwrap()
+        .stations
+        .into_iter()
+        .map(|station| station.into_owned())
+        .collect();
+
+    drop(string);
+
+    assert_eq!(stations[2].crs, "PBO");
+}
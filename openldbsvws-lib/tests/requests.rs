@@ -0,0 +1,242 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+use openldbsvws_lib::{
+    ApiVersion, BoardOperation, BoardOptions, BoardRequest, DeparturesOperation, DeparturesRequest,
+    DisruptionListRequest, FilterType, HistoricServiceDetailsRequest, HistoricTimelineRequest,
+    QueryOptions, QueryServicesRequest, ReasonCodeListRequest, ReasonCodeRequest,
+    ServiceDetailsRequest, SoapRequest, SourceInstanceNamesRequest, StationListRequest,
+    TocListRequest,
+};
+
+const TOKEN: &str = "00000000-0000-0000-0000-000000000000";
+
+fn time(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(time).unwrap()
+}
+
+/// Reads the envelope in `tests/fixtures/requests/{name}.xml`.
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/requests/{}.xml",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+
+    std::fs::read_to_string(path)
+        .unwrap()
+        .trim_end()
+        .to_string()
+}
+
+/// Checks that `request` serializes to the envelope in the fixture `name`.
+fn assert_envelope(name: &str, request: &impl SoapRequest) {
+    assert_eq!(request.envelope(TOKEN), fixture(name), "{}", name);
+}
+
+#[test]
+fn service_details() {
+    let request = ServiceDetailsRequest::new("202211157120613");
+
+    assert_eq!(
+        request.soap_action(),
+        "http://thalesgroup.com/RTTI/2012-01-13/ldbsv/GetServiceDetailsByRID"
+    );
+    assert_envelope("service_details", &request);
+}
+
+#[test]
+fn service_details_v12() {
+    let request = ServiceDetailsRequest::new("202211157120613");
+
+    assert_eq!(
+        request.envelope_for(TOKEN, ApiVersion::V12),
+        fixture("service_details_v12")
+    );
+}
+
+#[test]
+fn historic_service_details() {
+    let request =
+        HistoricServiceDetailsRequest::new("202211157120613", time("2022-11-15T08:30:00Z"));
+
+    assert_envelope("historic_service_details", &request);
+}
+
+#[test]
+fn historic_timeline() {
+    assert_envelope(
+        "historic_timeline",
+        &HistoricTimelineRequest::new("202211157120613"),
+    );
+}
+
+#[test]
+fn departure_board() {
+    let request =
+        BoardRequest::new(BoardOperation::DepartureByCrs, "KGX").with_options(BoardOptions {
+            num_rows: Some(20),
+            time: Some(time("2022-11-15T08:00:00Z")),
+            time_offset: Some(-15),
+            time_window: Some(60),
+            filter_crs: Some("YRK"),
+            filter_type: Some(FilterType::To),
+            non_passenger_services: true,
+            ..Default::default()
+        });
+
+    assert_envelope("departure_board", &request);
+}
+
+#[test]
+fn arrival_board_by_tiploc() {
+    let request =
+        BoardRequest::new(BoardOperation::ArrivalByTiploc, "KNGX").with_options(BoardOptions {
+            time: Some(time("2022-11-15T08:00:00Z")),
+            filter_crs: Some("YRK"),
+            filter_tiploc: Some("YORK"),
+            ..Default::default()
+        });
+
+    assert_envelope("arrival_board_by_tiploc", &request);
+}
+
+#[test]
+fn historic_departure_board() {
+    let request = BoardRequest::new(
+        BoardOperation::HistoricDeparture(time("2022-11-15T08:00:00Z")),
+        "KGX",
+    );
+
+    assert_envelope("historic_departure_board", &request);
+}
+
+#[test]
+fn times_are_in_uk_time() {
+    // During British Summer Time, 09:00 in Paris and 07:00 UTC are both 08:00 in London.
+    for given in ["2022-07-01T09:00:00+02:00", "2022-07-01T07:00:00Z"] {
+        let request =
+            BoardRequest::new(BoardOperation::DepartureByCrs, "KGX").with_options(BoardOptions {
+                time: Some(time(given)),
+                ..Default::default()
+            });
+
+        assert!(request
+            .envelope(TOKEN)
+            .contains("<ldb:time>2022-07-01T08:00:00</ldb:time>"));
+    }
+}
+
+#[test]
+fn next_departures() {
+    let request = DeparturesRequest::new(DeparturesOperation::Next, "KGX", &["YRK", "EDB"])
+        .with_options(BoardOptions {
+            time: Some(time("2022-11-15T08:00:00Z")),
+            ..Default::default()
+        });
+
+    assert!(request.validate().is_ok());
+    assert_envelope("next_departures", &request);
+}
+
+#[test]
+fn board_validation() {
+    let board = |options| {
+        BoardRequest::new(BoardOperation::DepartureByCrs, "KGX")
+            .with_options(options)
+            .validate()
+    };
+
+    assert!(board(BoardOptions::default()).is_ok());
+    assert!(board(BoardOptions {
+        num_rows: Some(150),
+        time_window: Some(1440),
+        ..Default::default()
+    })
+    .is_ok());
+    assert!(board(BoardOptions {
+        num_rows: Some(0),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(board(BoardOptions {
+        num_rows: Some(151),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(board(BoardOptions {
+        time_window: Some(1441),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn departures_validation() {
+    let destinations = ["YRK"; 26];
+
+    assert!(
+        DeparturesRequest::new(DeparturesOperation::Fastest, "KGX", &[])
+            .validate()
+            .is_err()
+    );
+    assert!(
+        DeparturesRequest::new(DeparturesOperation::Fastest, "KGX", &destinations)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        DeparturesRequest::new(DeparturesOperation::Fastest, "KGX", &destinations[..1])
+            .with_options(BoardOptions {
+                time_window: Some(1441),
+                ..Default::default()
+            })
+            .validate()
+            .is_err()
+    );
+    assert!(DisruptionListRequest::new(&[]).validate().is_err());
+}
+
+#[test]
+fn query_services() {
+    let request = QueryServicesRequest::new("1S05", NaiveDate::from_ymd_opt(2022, 11, 15).unwrap())
+        .with_options(QueryOptions {
+            filter_time: NaiveTime::from_hms_opt(8, 0, 0),
+            filter_crs: Some("KGX"),
+            filter_toc: Some("GR"),
+        });
+
+    assert_envelope("query_services", &request);
+}
+
+#[test]
+fn disruption_list() {
+    assert_envelope(
+        "disruption_list",
+        &DisruptionListRequest::new(&["KGX", "YRK"]),
+    );
+}
+
+#[test]
+fn reason_codes() {
+    assert_envelope("reason_code", &ReasonCodeRequest::new(104));
+    assert_envelope("reason_code_list", &ReasonCodeListRequest);
+    assert_envelope("source_instance_names", &SourceInstanceNamesRequest);
+}
+
+#[test]
+fn reference_data() {
+    let request = TocListRequest::new().with_current_version(Some("1.0"));
+
+    assert_eq!(
+        request.soap_action(),
+        "http://thalesgroup.com/RTTI/2015-05-14/ldbsv_ref/GetTOCList"
+    );
+    assert_envelope("toc_list", &request);
+    assert_envelope("station_list", &StationListRequest::new());
+}
+
+#[test]
+fn escaping() {
+    let request = ServiceDetailsRequest::new("<rid> & \"more\"");
+
+    assert_eq!(request.envelope("a&b<c>'d'"), fixture("escaping"));
+}
@@ -0,0 +1,137 @@
+//! Checks that types which can't be snapshotted straight from a response survive serialization.
+
+use chrono::Duration;
+use openldbsvws_lib::{
+    DeparturesBoard, DisruptionList, LatenessTolerance, ReasonCodeList, ServiceDetails,
+    ServiceList, SourceInstanceList, StationBoard, StationList, TocList, UserLateness,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+
+/// Serializes `value` to JSON and back, checking that nothing is lost. The JSON is dropped before the result is
+/// compared, so this also checks that nothing borrows from it.
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+    let json = serde_json::to_string(value).unwrap();
+    let deserialized: T = serde_json::from_str(&json).unwrap();
+
+    drop(json);
+
+    assert_eq!(
+        serde_json::to_value(&deserialized).unwrap(),
+        serde_json::to_value(value).unwrap()
+    );
+}
+
+/// Reads the fixture `name` from `tests/fixtures/responses`.
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/responses/{}.xml",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn owned_responses_round_trip() {
+    // Disruption messages have HTML in them, so their JSON is full of escapes, which can't be borrowed.
+    round_trip(
+        &DisruptionList::try_from(fixture("disruptions").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &ServiceDetails::try_from(fixture("service_associations").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &StationBoard::try_from(fixture("board_departures").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &DeparturesBoard::try_from(fixture("departures_next").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &ServiceList::try_from(fixture("query_services").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &ReasonCodeList::try_from(fixture("reason_code_list").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &SourceInstanceList::try_from(fixture("source_instances").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &TocList::try_from(fixture("toc_list").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+    round_trip(
+        &StationList::try_from(fixture("station_list").as_str())
+            .unwrap()
+            .into_owned(),
+    );
+}
+
+#[test]
+fn tolerances_are_in_seconds() {
+    let tolerance = LatenessTolerance::minute();
+    let value = serde_json::to_value(&tolerance).unwrap();
+
+    assert_eq!(value, json!({ "early": 60, "late": 60 }));
+    assert_eq!(
+        serde_json::from_value::<LatenessTolerance>(value).unwrap(),
+        tolerance
+    );
+
+    let tolerance = LatenessTolerance::ppm_long_distance();
+    let value = serde_json::to_value(&tolerance).unwrap();
+
+    assert_eq!(value, json!({ "early": null, "late": 600 }));
+    assert_eq!(
+        serde_json::from_value::<LatenessTolerance>(value).unwrap(),
+        tolerance
+    );
+}
+
+#[test]
+fn durations_out_of_range_are_rejected() {
+    assert!(serde_json::from_value::<LatenessTolerance>(
+        json!({ "early": null, "late": i64::MAX })
+    )
+    .is_err());
+    assert_eq!(
+        serde_json::from_value::<LatenessTolerance>(json!({ "early": -30, "late": 59 })).unwrap(),
+        LatenessTolerance {
+            early: Some(Duration::seconds(-30)),
+            late: Duration::seconds(59),
+        }
+    );
+}
+
+#[test]
+fn lateness_is_in_seconds() {
+    assert_eq!(
+        serde_json::to_value(UserLateness::Late(Duration::minutes(5))).unwrap(),
+        json!({ "Late": 300 })
+    );
+    assert_eq!(
+        serde_json::from_value::<UserLateness>(json!({ "Early": -120 })).unwrap(),
+        UserLateness::Early(Duration::minutes(-2))
+    );
+    assert_eq!(
+        serde_json::from_value::<UserLateness>(json!("Delayed")).unwrap(),
+        UserLateness::Delayed
+    );
+}
@@ -0,0 +1,91 @@
+//! Parses each response in `tests/fixtures/responses` and compares the result, serialized to JSON, against its
+//! snapshot in `tests/snapshots`. Errors are compared by their message. This needs the `serde` feature, which the
+//! library's dev-dependency on itself turns on.
+//!
+//! After an intended change to the output, run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshots, then review the
+//! diff before committing.
+
+use std::fs;
+use std::path::PathBuf;
+
+use openldbsvws_lib::{
+    DeparturesBoard, DisruptionList, ReasonCode, ReasonCodeList, ServiceDetails, ServiceList,
+    SourceInstanceList, StationBoard, StationList, Timeline, TocList,
+};
+use serde_json::{json, Value};
+
+fn path(directory: &str, name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(directory)
+        .join(format!("{}.{}", name, extension))
+}
+
+/// Parses the fixture `name` and checks the result against its snapshot.
+fn check(name: &str, parse: impl Fn(&str) -> Result<Value, String>) {
+    let fixture = fs::read_to_string(path("fixtures/responses", name, "xml")).unwrap();
+    let actual = match parse(&fixture) {
+        Ok(value) => value,
+        Err(error) => json!({ "error": error }),
+    };
+
+    let snapshot = path("snapshots", name, "json");
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(
+            &snapshot,
+            serde_json::to_string_pretty(&actual).unwrap() + "\n",
+        )
+        .unwrap();
+        return;
+    }
+
+    let expected: Value = match fs::read_to_string(&snapshot) {
+        Ok(expected) => serde_json::from_str(&expected).unwrap(),
+        Err(_) => panic!(
+            "no snapshot for {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            name
+        ),
+    };
+
+    assert!(
+        actual == expected,
+        "{} doesn't match its snapshot, got:\n{}",
+        name,
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+}
+
+macro_rules! snapshot {
+    ($name:ident, $parse:expr) => {
+        #[test]
+        fn $name() {
+            check(stringify!($name), |string| {
+                $parse(string)
+                    .map(|parsed| serde_json::to_value(&parsed).unwrap())
+                    .map_err(|error| error.to_string())
+            })
+        }
+    };
+}
+
+snapshot!(service_details, ServiceDetails::try_from);
+snapshot!(service_historic, ServiceDetails::try_from);
+snapshot!(service_cancelled, ServiceDetails::try_from);
+snapshot!(service_associations, ServiceDetails::try_from);
+snapshot!(service_false_destination, ServiceDetails::try_from);
+snapshot!(service_bus, ServiceDetails::try_from);
+snapshot!(service_lenient, ServiceDetails::parse_lenient);
+snapshot!(fault_invalid_token, ServiceDetails::try_from);
+snapshot!(fault_server, StationBoard::try_from);
+snapshot!(board_departures, StationBoard::try_from);
+snapshot!(board_tiploc_passes, StationBoard::try_from);
+snapshot!(departures_next, DeparturesBoard::try_from);
+snapshot!(disruptions, DisruptionList::try_from);
+snapshot!(timeline, Timeline::try_from);
+snapshot!(query_services, ServiceList::try_from);
+snapshot!(reason_code, ReasonCode::try_from);
+snapshot!(reason_code_list, ReasonCodeList::try_from);
+snapshot!(source_instances, SourceInstanceList::try_from);
+snapshot!(toc_list, TocList::try_from);
+snapshot!(station_list, StationList::try_from);
@@ -0,0 +1,22 @@
+use chrono::DateTime;
+use openldbsvws_lib::uk_time;
+
+fn uk(time: &str) -> String {
+    uk_time(&DateTime::parse_from_rfc3339(time).unwrap()).to_rfc3339()
+}
+
+#[test]
+fn times_are_converted_to_uk_time() {
+    // During British Summer Time, 09:00 in Paris and 07:00 UTC are both 08:00 in London.
+    assert_eq!(uk("2022-07-01T09:00:00+02:00"), "2022-07-01T08:00:00+01:00");
+    assert_eq!(uk("2022-07-01T07:00:00Z"), "2022-07-01T08:00:00+01:00");
+    assert_eq!(uk("2022-12-01T07:00:00-05:00"), "2022-12-01T12:00:00+00:00");
+}
+
+#[test]
+fn summer_time_starts_and_ends_at_one_utc() {
+    assert_eq!(uk("2022-03-27T00:59:59Z"), "2022-03-27T00:59:59+00:00");
+    assert_eq!(uk("2022-03-27T01:00:00Z"), "2022-03-27T02:00:00+01:00");
+    assert_eq!(uk("2022-10-30T00:59:59Z"), "2022-10-30T01:59:59+01:00");
+    assert_eq!(uk("2022-10-30T01:00:00Z"), "2022-10-30T01:00:00+00:00");
+}
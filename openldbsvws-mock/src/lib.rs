@@ -0,0 +1,408 @@
+//! A mock OpenLDBSVWS server, which serves canned responses so that clients can be tested without the live API.
+//!
+//! Responses are read from a fixture directory, keyed by operation and the identifying parameter of the request, such
+//! as the RID or CRS code. For a `GetServiceDetailsByRID` request for RID `202209207612345`, the server tries these
+//! fixtures in order:
+//!
+//! - `GetServiceDetailsByRID/202209207612345.xml`
+//! - `GetServiceDetailsByRID/default.xml`
+//! - `GetServiceDetailsByRID.xml`
+//!
+//! Requests without a valid access token are rejected with a 401 and a SOAP fault, as the live API does. Faults,
+//! latency and malformed XML can be injected for every operation or for a single one.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use openldbsvws_lib::{escape, SOAP_NAMESPACE};
+use roxmltree::Document;
+
+/// The token the server accepts if no other is set.
+pub const DEFAULT_TOKEN: &str = "00000000-0000-0000-0000-000000000000";
+
+/// The parameters which identify what a request is for, in order of preference. The first one present keys the
+/// fixture.
+const KEY_PARAMETERS: [&str; 5] = ["rid", "serviceID", "crs", "tiploc", "reasonCode"];
+
+/// Something to do to responses rather than serving them as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Injection {
+    /// Respond with a SOAP fault and an HTTP status instead of the fixture.
+    Fault {
+        /// The HTTP status, such as 500.
+        status: u16,
+        /// The fault code, such as "soap:Client".
+        code: String,
+        /// The description of the fault.
+        message: String,
+    },
+    /// Wait before responding.
+    Latency(Duration),
+    /// Cut the fixture off halfway, so that it isn't well-formed XML.
+    MalformedXml,
+}
+
+impl Injection {
+    /// A fault blaming the server, as returned when the API fails.
+    pub fn server_fault(message: impl Into<String>) -> Self {
+        Injection::Fault {
+            status: 500,
+            code: "soap:Server".to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// A fault blaming the client, as returned for invalid requests.
+    pub fn client_fault(message: impl Into<String>) -> Self {
+        Injection::Fault {
+            status: 500,
+            code: "soap:Client".to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A response to send.
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    /// Makes a response containing a SOAP fault.
+    fn fault(status: u16, code: &str, message: &str) -> Self {
+        Response {
+            status,
+            body: format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?><soap:Envelope xmlns:soap=\"{}\"><soap:Body><soap:Fault>\
+                 <faultcode>{}</faultcode><faultstring>{}</faultstring><detail/></soap:Fault></soap:Body>\
+                 </soap:Envelope>",
+                SOAP_NAMESPACE,
+                escape(code),
+                escape(message)
+            ),
+        }
+    }
+
+    /// The reason phrase of the status.
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            429 => "Too Many Requests",
+            500 => "Internal Server Error",
+            503 => "Service Unavailable",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// What a request asks for.
+struct Request {
+    operation: String,
+    token: Option<String>,
+    key: Option<String>,
+}
+
+impl Request {
+    /// Reads the operation, token and key of a SOAP envelope.
+    fn parse(body: &str) -> Option<Request> {
+        let document = Document::parse(body).ok()?;
+        let request = document
+            .root()
+            .descendants()
+            .find(|x| x.has_tag_name("Body"))?
+            .children()
+            .find(|x| x.is_element())?;
+
+        let operation = request.tag_name().name();
+        let text = |name: &str| {
+            request
+                .descendants()
+                .find(|x| x.has_tag_name(name))
+                .and_then(|x| x.text())
+                .map(|x| x.trim().to_string())
+        };
+
+        Some(Request {
+            operation: operation
+                .strip_suffix("Request")
+                .unwrap_or(operation)
+                .to_string(),
+            token: document
+                .root()
+                .descendants()
+                .find(|x| x.has_tag_name("TokenValue"))
+                .and_then(|x| x.text())
+                .map(|x| x.trim().to_string()),
+            key: KEY_PARAMETERS
+                .iter()
+                .find_map(|name| text(name))
+                // Keys become file names, so anything which could escape the directory is ignored.
+                .filter(|key| {
+                    !key.is_empty()
+                        && key
+                            .bytes()
+                            .all(|x| x.is_ascii_alphanumeric() || x == b'-' || x == b'_')
+                }),
+        })
+    }
+}
+
+/// A mock OpenLDBSVWS server.
+///
+/// The server is configured with builder methods, then either run on the current thread with `serve`, or in the
+/// background with `start`.
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    fixtures: PathBuf,
+    tokens: Vec<String>,
+    injections: Vec<(Option<String>, Injection)>,
+}
+
+impl MockServer {
+    /// Creates a server for the fixtures in `fixtures`, accepting `DEFAULT_TOKEN`.
+    pub fn new(fixtures: impl Into<PathBuf>) -> Self {
+        MockServer {
+            fixtures: fixtures.into(),
+            tokens: vec![DEFAULT_TOKEN.to_string()],
+            injections: Vec::new(),
+        }
+    }
+
+    /// Accepts `token` rather than the default token. Can be called more than once to accept several tokens.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        if self.tokens == [DEFAULT_TOKEN] {
+            self.tokens.clear();
+        }
+
+        self.tokens.push(token.into());
+        self
+    }
+
+    /// Applies `injection` to responses to `operation`, such as "GetServiceDetailsByRID". If None, it is applied to
+    /// every operation.
+    pub fn with_injection(mut self, operation: Option<&str>, injection: Injection) -> Self {
+        self.injections
+            .push((operation.map(|x| x.to_string()), injection));
+        self
+    }
+
+    /// Finds the fixture for a request.
+    fn fixture(&self, request: &Request) -> Option<PathBuf> {
+        let directory = self.fixtures.join(&request.operation);
+        let mut candidates = Vec::new();
+
+        if let Some(key) = &request.key {
+            candidates.push(directory.join(format!("{}.xml", key)));
+        }
+
+        candidates.push(directory.join("default.xml"));
+        candidates.push(self.fixtures.join(format!("{}.xml", request.operation)));
+
+        candidates.into_iter().find(|x| x.is_file())
+    }
+
+    /// Works out the response to a request body.
+    fn respond(&self, body: &str) -> Response {
+        let request = match Request::parse(body) {
+            Some(request) => request,
+            None => {
+                return Response::fault(400, "soap:Client", "The request is not a SOAP envelope")
+            }
+        };
+
+        if !matches!(&request.token, Some(token) if self.tokens.contains(token)) {
+            return Response::fault(401, "soap:Client", "Unauthorized: invalid access token");
+        }
+
+        let injections = self
+            .injections
+            .iter()
+            .filter(|(operation, _)| match operation {
+                Some(operation) => *operation == request.operation,
+                None => true,
+            })
+            .map(|(_, injection)| injection);
+
+        let mut malformed = false;
+
+        for injection in injections {
+            match injection {
+                Injection::Latency(duration) => thread::sleep(*duration),
+                Injection::MalformedXml => malformed = true,
+                Injection::Fault {
+                    status,
+                    code,
+                    message,
+                } => return Response::fault(*status, code, message),
+            }
+        }
+
+        let fixture = match self.fixture(&request) {
+            Some(fixture) => fixture,
+            None => {
+                return Response::fault(
+                    500,
+                    "soap:Client",
+                    &format!(
+                        "No fixture for {} {}",
+                        request.operation,
+                        request.key.as_deref().unwrap_or("")
+                    ),
+                )
+            }
+        };
+
+        match std::fs::read_to_string(&fixture) {
+            Ok(mut body) => {
+                if malformed {
+                    let mut half = body.len() / 2;
+
+                    while !body.is_char_boundary(half) {
+                        half -= 1;
+                    }
+
+                    body.truncate(half);
+                }
+
+                Response { status: 200, body }
+            }
+            Err(e) => Response::fault(500, "soap:Server", &e.to_string()),
+        }
+    }
+
+    /// Reads a request from a connection and writes the response.
+    fn handle(&self, stream: TcpStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut length = 0;
+        let mut line = String::new();
+
+        // The request line, then headers up to a blank line.
+        reader.read_line(&mut line)?;
+
+        loop {
+            line.clear();
+
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+
+        let response = self.respond(&String::from_utf8_lossy(&body));
+        let mut stream = stream;
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.reason(),
+            response.body.len(),
+            response.body
+        )?;
+
+        stream.flush()
+    }
+
+    /// Serves requests from `listener` on the current thread, forever.
+    pub fn serve(self, listener: TcpListener) -> std::io::Result<()> {
+        let server = Arc::new(self);
+
+        for stream in listener.incoming() {
+            let server = server.clone();
+            let stream = stream?;
+
+            thread::spawn(move || server.handle(stream));
+        }
+
+        Ok(())
+    }
+
+    /// Starts serving on `addr` in the background. Use port 0 to pick any free port. The server stops when the
+    /// returned handle is dropped.
+    pub fn start(self, addr: &str) -> std::io::Result<RunningServer> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = Arc::new(self);
+
+        let thread = {
+            let stopped = stopped.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    if let Ok(stream) = stream {
+                        let server = server.clone();
+
+                        thread::spawn(move || server.handle(stream));
+                    }
+                }
+            })
+        };
+
+        Ok(RunningServer {
+            addr,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// The directory fixtures are read from.
+    pub fn fixtures(&self) -> &Path {
+        &self.fixtures
+    }
+}
+
+/// A mock server running in the background.
+#[derive(Debug)]
+pub struct RunningServer {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RunningServer {
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The endpoint to send requests to. The server ignores the path, so this also works as the reference data
+    /// endpoint.
+    pub fn endpoint(&self) -> String {
+        format!("http://{}/OpenLDBSVWS/ldbsv13.asmx", self.addr)
+    }
+}
+
+impl Drop for RunningServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+
+        // Wake the listener up so it notices it has been stopped.
+        let _ = TcpStream::connect(self.addr);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::net::TcpListener;
+use std::time::Duration;
+
+use clap::Command;
+
+use openldbsvws_mock::{Injection, MockServer};
+
+fn main() -> std::io::Result<()> {
+    let matches = Command::new("mock-server")
+        .about("serves canned openldbsvws responses from a fixture directory")
+        .version("0.1.0")
+        .arg(clap::arg!(<FIXTURES> "Directory of fixtures").required(true))
+        .arg(
+            clap::arg!(--addr <ADDR> "Address to listen on, defaults to 127.0.0.1:8080")
+                .id("ADDR")
+                .required(false),
+        )
+        .arg(
+            clap::arg!(-t <TOKEN> "Token to accept, may be repeated")
+                .id("TOKEN")
+                .required(false)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            clap::arg!(--operation <OPERATION> "Only inject into this operation")
+                .id("OPERATION")
+                .required(false),
+        )
+        .arg(
+            clap::arg!(--fault <MESSAGE> "Respond with a server fault")
+                .id("FAULT")
+                .required(false),
+        )
+        .arg(
+            clap::arg!(--latency <MILLISECONDS> "Wait before responding")
+                .id("LATENCY")
+                .required(false)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            clap::arg!(--malformed "Respond with malformed XML")
+                .id("MALFORMED")
+                .takes_value(false),
+        )
+        .get_matches();
+
+    let fixtures = matches.get_one::<String>("FIXTURES").expect("required");
+    let addr = matches
+        .get_one::<String>("ADDR")
+        .map(|addr| addr.as_str())
+        .unwrap_or("127.0.0.1:8080");
+    let operation = matches
+        .get_one::<String>("OPERATION")
+        .map(|operation| operation.as_str());
+
+    let mut server = MockServer::new(fixtures);
+
+    for token in matches.get_many::<String>("TOKEN").into_iter().flatten() {
+        server = server.with_token(token);
+    }
+
+    if let Some(latency) = matches.get_one::<u64>("LATENCY") {
+        server = server.with_injection(
+            operation,
+            Injection::Latency(Duration::from_millis(*latency)),
+        );
+    }
+
+    if let Some(message) = matches.get_one::<String>("FAULT") {
+        server = server.with_injection(operation, Injection::server_fault(message));
+    }
+
+    if matches.is_present("MALFORMED") {
+        server = server.with_injection(operation, Injection::MalformedXml);
+    }
+
+    let listener = TcpListener::bind(addr)?;
+
+    eprintln!(
+        "serving {} on http://{}",
+        server.fixtures().display(),
+        listener.local_addr()?
+    );
+
+    server.serve(listener)
+}
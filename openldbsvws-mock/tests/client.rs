@@ -0,0 +1,195 @@
+use std::future::Future;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use openldbsvws_lib::{ApiError, BoardOptions, FetchError, LdbsvClient, QueryOptions};
+use openldbsvws_mock::{Injection, MockServer, RunningServer, DEFAULT_TOKEN};
+use tokio::runtime::Builder;
+
+const RID: &str = "202209207612345";
+
+fn server() -> MockServer {
+    MockServer::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+}
+
+fn client(server: &RunningServer) -> LdbsvClient {
+    LdbsvClient::new(DEFAULT_TOKEN)
+        .with_endpoint(server.endpoint())
+        .with_reference_endpoint(server.endpoint())
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn service_details() {
+    let server = server().start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let details = client.get_service_details(RID).await.unwrap();
+
+        assert_eq!(details.rid, RID);
+        assert_eq!(details.locations.len(), 4);
+    });
+}
+
+#[test]
+fn results_are_sent_between_tasks() {
+    let server = server().start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async move {
+        // Spawning needs the result, including any error, to be Send and 'static.
+        let details = tokio::spawn(async move { client.get_service_details(RID).await })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(details.rid, RID);
+    });
+}
+
+#[test]
+fn query_then_reasons() {
+    let server = server().start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let sdd = NaiveDate::from_ymd_opt(2022, 9, 20).unwrap();
+        let list = client
+            .query_services("1A23", sdd, &QueryOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(list.services[0].rid, RID);
+
+        let reasons = client.get_reason_code_list().await.unwrap();
+
+        assert_eq!(reasons.reasons.len(), 2);
+    });
+}
+
+#[test]
+fn boards() {
+    let server = server().start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let board = client
+            .get_departure_board_by_crs("BRI", &BoardOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(board.train_services.len(), 1);
+
+        let departures = client
+            .get_next_departures_by_crs("RDG", &["PAD", "OXF"], &BoardOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(departures.departures.len(), 2);
+    });
+}
+
+#[test]
+fn missing_fixture() {
+    let server = server().start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let error = client
+            .get_service_details("202209200000000")
+            .await
+            .unwrap_err();
+
+        // The mock blames the request when it has no fixture, which for a service means it wasn't found.
+        assert!(matches!(
+            error,
+            FetchError::Api(ApiError::ServiceNotFound(_))
+        ));
+
+        let error = client
+            .get_departure_board_by_crs("XXX", &BoardOptions::default())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            FetchError::Api(ApiError::InvalidRequest(_))
+        ));
+    });
+}
+
+#[test]
+fn invalid_token() {
+    let server = server().with_token("secret").start("127.0.0.1:0").unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let error = client.get_service_details(RID).await.unwrap_err();
+
+        assert!(matches!(error, FetchError::Api(ApiError::InvalidToken(_))));
+    });
+}
+
+#[test]
+fn injected_fault() {
+    let server = server()
+        .with_injection(
+            Some("GetServiceDetailsByRID"),
+            Injection::server_fault("Something broke"),
+        )
+        .start("127.0.0.1:0")
+        .unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let error = client.get_service_details(RID).await.unwrap_err();
+
+        match error {
+            FetchError::Api(ApiError::ServerError(fault)) => {
+                assert_eq!(fault.message, "Something broke")
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+
+        // Other operations are unaffected.
+        assert!(client.get_reason_code_list().await.is_ok());
+    });
+}
+
+#[test]
+fn malformed_xml() {
+    let server = server()
+        .with_injection(None, Injection::MalformedXml)
+        .start("127.0.0.1:0")
+        .unwrap();
+    let client = client(&server);
+
+    block_on(async {
+        let error = client.get_service_details(RID).await.unwrap_err();
+
+        assert!(matches!(error, FetchError::ParseXMLError { .. }));
+    });
+}
+
+#[test]
+fn latency() {
+    let server = server()
+        .with_injection(None, Injection::Latency(Duration::from_millis(500)))
+        .start("127.0.0.1:0")
+        .unwrap();
+    let client = client(&server).with_timeout(Duration::from_millis(100));
+
+    block_on(async {
+        let error = client.get_service_details(RID).await.unwrap_err();
+
+        assert!(matches!(error, FetchError::RequestError { .. }));
+    });
+}
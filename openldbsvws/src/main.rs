@@ -1,18 +1,256 @@
-use std::time::Duration;
+use std::fs;
+use std::io;
 
-use anyhow::Result;
-use clap::Command;
-use reqwest::Client;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, Utc};
+use clap::{ArgMatches, Command};
 use tokio::runtime::Builder;
 
-use openldbsvws_lib::{PrettyPrintable, ServiceDetails};
+use openldbsvws_lib::{
+    uk_time, ApiError, ApiVersion, BoardOperation, BoardOptions, BoardRequest, Crs,
+    DeparturesOperation, DeparturesRequest, DisruptionListRequest, FetchError, FilterType,
+    Headcode, HistoricTimelineRequest, InvalidIdentifier, LdbsvClient, PrettyOptions,
+    PrettyPrintable, QueryOptions, QueryServicesRequest, ReasonCatalogue, ReasonCodeListRequest,
+    ReasonCodeRequest, ReferenceData, Rid, ServiceDetailsRequest, SourceInstanceNamesRequest,
+    StationListRequest, Tiploc, TocListRequest, Uid,
+};
 
-macro_rules! service_details {
-    () => {"<soapenv:Envelope xmlns:soapenv=\"http://schemas.xmlsoap.org/soap/envelope/\" xmlns:typ=\"http://thalesgroup.com/RTTI/2013-11-28/Token/types\" xmlns:ldb=\"http://thalesgroup.com/RTTI/2021-11-01/ldbsv/\"><soapenv:Header><typ:AccessToken><typ:TokenValue>{token}</typ:TokenValue></typ:AccessToken></soapenv:Header><soapenv:Body><ldb:GetServiceDetailsByRIDRequest><ldb:rid>{rid}</ldb:rid></ldb:GetServiceDetailsByRIDRequest></soapenv:Body></soapenv:Envelope>"}
+/// Turns an error from a request into a message saying what went wrong and what to do about it.
+fn explain(error: FetchError) -> anyhow::Error {
+    match error {
+        FetchError::Api(ApiError::InvalidToken(_)) => anyhow!(
+            "the token was rejected, check it is a token for the LDB Webservice (Staff Version)"
+        ),
+        FetchError::Api(ApiError::ServiceNotFound(_)) => {
+            anyhow!("the service was not found, check the RID or headcode and date")
+        }
+        FetchError::Api(ApiError::RateLimited(_)) => {
+            anyhow!("too many requests have been made with this token, try again later")
+        }
+        FetchError::Api(ApiError::InvalidRequest(fault)) => {
+            anyhow!("the request was rejected: {}", fault.message)
+        }
+        FetchError::Api(ApiError::ServerError(fault)) => {
+            anyhow!(
+                "National Rail's server failed, try again later ({})",
+                fault.message
+            )
+        }
+        error => anyhow!("{}", error),
+    }
+}
+
+/// Creates a client with the token, API version and endpoints given to a subcommand.
+fn client(sub_matches: &ArgMatches) -> LdbsvClient {
+    let token = sub_matches.get_one::<String>("TOKEN").expect("required");
+    let mut client = LdbsvClient::new(token);
+
+    // The version sets its own endpoint, so it has to come before any override.
+    if let Some(version) = sub_matches.get_one::<ApiVersion>("API_VERSION") {
+        client = client.with_version(*version);
+    }
+
+    if let Some(endpoint) = sub_matches.get_one::<String>("ENDPOINT") {
+        client = client.with_endpoint(endpoint);
+    }
+
+    if let Some(endpoint) = sub_matches.get_one::<String>("REFERENCE_ENDPOINT") {
+        client = client.with_reference_endpoint(endpoint);
+    }
+
+    client
+}
+
+/// Parses a CRS code argument. Codes are upper case, but are accepted in any case.
+fn crs(value: &str) -> Result<Crs<'static>, InvalidIdentifier> {
+    value.to_ascii_uppercase().parse()
+}
+
+/// Loads the reference data file given with `--reference-data`, if any.
+fn reference_data(sub_matches: &ArgMatches) -> Result<Option<ReferenceData>> {
+    match sub_matches.get_one::<String>("REFERENCE_DATA") {
+        Some(file) => {
+            let contents = fs::read_to_string(file)
+                .map_err(|e| anyhow!("couldn't read reference data from {}: {}", file, e))?;
+
+            Ok(Some(serde_json::from_str(&contents)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Checks that a CRS code is a known station, if there is reference data to check it against.
+fn check_station(crs: &str, reference: Option<&ReferenceData>) -> Result<()> {
+    match reference {
+        Some(reference) if !reference.is_valid_crs(crs) => {
+            Err(anyhow!("{} isn't a station in the reference data", crs))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks a location argument, which is a CRS code, or a TIPLOC with `--tiploc`, so that a malformed one is rejected
+/// before making a request. CRS codes are also checked against the reference data, if any. Returns the location in
+/// upper case.
+fn check_location(value: &str, tiploc: bool, reference: Option<&ReferenceData>) -> Result<String> {
+    let value = value.to_ascii_uppercase();
+
+    if tiploc {
+        Tiploc::new(&value)?;
+    } else {
+        Crs::new(&value)?;
+        check_station(&value, reference)?;
+    }
+
+    Ok(value)
+}
+
+fn board_command(name: &'static str, about: &'static str) -> Command<'static> {
+    Command::new(name)
+        .about(about)
+        .arg(clap::arg!(<LOCATION> "CRS code, or TIPLOC with --tiploc").required(true))
+        .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
+        .arg(clap::arg!(--json).id("JSON").takes_value(false))
+        .arg(
+            clap::arg!(--tiploc "Treat locations as TIPLOCs rather than CRS codes")
+                .id("TIPLOC")
+                .takes_value(false),
+        )
+        .arg(
+            clap::arg!(--all "Include non-passenger services")
+                .id("ALL")
+                .takes_value(false),
+        )
+        .arg(
+            clap::arg!(--filter <LOCATION> "Only show services calling at this location")
+                .id("FILTER")
+                .required(false),
+        )
+        .arg(
+            clap::arg!(--from "Only show services which called at the filter station")
+                .id("FROM")
+                .takes_value(false),
+        )
+        .arg(
+            clap::arg!(--offset <MINUTES> "Offset from now, which may be negative")
+                .id("OFFSET")
+                .required(false)
+                .allow_hyphen_values(true)
+                .value_parser(clap::value_parser!(i32)),
+        )
+        .arg(
+            clap::arg!(--window <MINUTES> "Number of minutes to show services for")
+                .id("WINDOW")
+                .required(false)
+                .value_parser(clap::value_parser!(u16)),
+        )
+        .arg(
+            clap::arg!(--working "Show working times rather than public times")
+                .id("WORKING")
+                .takes_value(false),
+        )
+        .arg(
+            clap::arg!(--strict "Fail on any problem with the response, rather than warning")
+                .id("STRICT")
+                .takes_value(false),
+        )
+        .arg(
+            clap::arg!(--rows <ROWS> "Maximum number of services to show")
+                .id("ROWS")
+                .required(false)
+                .value_parser(clap::value_parser!(u16)),
+        )
+}
+
+/// Reads the options of a board command. `filter` is the checked `--filter` location, if any.
+fn board_options<'a>(sub_matches: &ArgMatches, filter: Option<&'a str>) -> BoardOptions<'a> {
+    let tiploc = sub_matches.is_present("TIPLOC");
+
+    BoardOptions {
+        num_rows: sub_matches.get_one::<u16>("ROWS").copied(),
+        time: None,
+        time_offset: sub_matches.get_one::<i32>("OFFSET").copied(),
+        time_window: sub_matches.get_one::<u16>("WINDOW").copied(),
+        filter_crs: if tiploc { None } else { filter },
+        filter_tiploc: if tiploc { filter } else { None },
+        filter_type: Some(if sub_matches.is_present("FROM") {
+            FilterType::From
+        } else {
+            FilterType::To
+        }),
+        non_passenger_services: sub_matches.is_present("ALL"),
+    }
 }
 
-macro_rules! arrival_details {
-    () => {"<soapenv:Envelope xmlns:soapenv=\"http://schemas.xmlsoap.org/soap/envelope/\" xmlns:typ=\"http://thalesgroup.com/RTTI/2013-11-28/Token/types\" xmlns:ldb=\"http://thalesgroup.com/RTTI/2021-11-01/ldb/\"><soapenv:Header><typ:AccessToken><typ:TokenValue>{token}</typ:TokenValue></typ:AccessToken></soapenv:Header><soapenv:Body><ldb:GetArrivalBoardRequest><ldb:numRows>150</ldb:numRows><ldb:crs>{crs}</ldb:crs><ldb:filterCrs>{filter_crs}</ldb:filterCrs><ldb:filterType>{filter_type}</ldb:filterType><ldb:timeOffset>{time_offset}</ldb:timeOffset><ldb:timeWindow>{time_window}</ldb:timeWindow></ldb:GetArrivalBoardRequest></soapenv:Body></soapenv:Envelope>"}
+/// The operations `record` can make, and the parameters they take.
+const RECORD_OPERATIONS: &str = "service <RID>, timeline <RID>, arrivals <LOCATION>, departures <LOCATION>, \
+board <LOCATION>, next <CRS> <CRS>..., query <SERVICE>, disruptions <CRS>..., reason <CODE>, reasons, sources, tocs, \
+stations";
+
+/// Makes the request for an operation of `record`, returning the raw response.
+async fn record(
+    client: &LdbsvClient,
+    operation: &str,
+    parameters: &[&str],
+    date: NaiveDate,
+    tiploc: bool,
+) -> Result<String> {
+    let first = || {
+        parameters
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("{} needs a parameter", operation))
+    };
+    let board = |operation| {
+        let operation = match (operation, tiploc) {
+            ("arrivals", false) => BoardOperation::ArrivalByCrs,
+            ("arrivals", true) => BoardOperation::ArrivalByTiploc,
+            ("departures", false) => BoardOperation::DepartureByCrs,
+            ("departures", true) => BoardOperation::DepartureByTiploc,
+            (_, false) => BoardOperation::ArrivalDepartureByCrs,
+            (_, true) => BoardOperation::ArrivalDepartureByTiploc,
+        };
+
+        first().map(|location| BoardRequest::new(operation, location))
+    };
+
+    let body = match operation {
+        "service" => client.send(&ServiceDetailsRequest::new(first()?)).await,
+        "timeline" => client.send(&HistoricTimelineRequest::new(first()?)).await,
+        "arrivals" | "departures" | "board" => client.send(&board(operation)?).await,
+        "next" => {
+            let request =
+                DeparturesRequest::new(DeparturesOperation::Next, first()?, &parameters[1..]);
+            client.send(&request).await
+        }
+        "query" => {
+            client
+                .send(&QueryServicesRequest::new(first()?, date))
+                .await
+        }
+        "disruptions" => client.send(&DisruptionListRequest::new(parameters)).await,
+        "reason" => {
+            let code = first()?;
+            let code = code
+                .parse()
+                .map_err(|_| anyhow!("reason codes are numbers, got {}", code))?;
+
+            client.send(&ReasonCodeRequest::new(code)).await
+        }
+        "reasons" => client.send(&ReasonCodeListRequest).await,
+        "sources" => client.send(&SourceInstanceNamesRequest).await,
+        "tocs" => client.send(&TocListRequest::new()).await,
+        "stations" => client.send(&StationListRequest::new()).await,
+        _ => {
+            return Err(anyhow!(
+                "unknown operation {}, expected one of {}",
+                operation,
+                RECORD_OPERATIONS
+            ))
+        }
+    };
+
+    body.map_err(explain)
 }
 
 fn main() -> Result<()> {
@@ -20,57 +258,474 @@ fn main() -> Result<()> {
         .subcommand_required(true)
         .about("query data from openldbsvws")
         .version("0.1.0")
+        .arg(
+            clap::arg!(--"api-version" <VERSION> "Schema version to use, 12 or 13, defaults to 13")
+                .id("API_VERSION")
+                .required(false)
+                .global(true)
+                .value_parser(clap::value_parser!(ApiVersion)),
+        )
+        .arg(
+            clap::arg!(--endpoint <URL> "Endpoint to send requests to, such as a mock server")
+                .id("ENDPOINT")
+                .required(false)
+                .global(true),
+        )
+        .arg(
+            clap::arg!(--"reference-endpoint" <URL> "Endpoint to send reference data requests to")
+                .id("REFERENCE_ENDPOINT")
+                .required(false)
+                .global(true),
+        )
+        .arg(
+            clap::arg!(--"reference-data" <FILE> "Reference data file, to check CRS codes and name stations")
+                .id("REFERENCE_DATA")
+                .required(false)
+                .global(true),
+        )
         .subcommand(
             Command::new("service")
                 .about("Gets information about a service")
-                .arg(clap::arg!(<SERVICE>).required(true))
+                .arg(clap::arg!(<SERVICE> "RID, or headcode or UID with --date").required(true))
+                .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
+                .arg(clap::arg!(--json).id("JSON").takes_value(false))
+                .arg(
+                    clap::arg!(--working "Show working times rather than public times")
+                        .id("WORKING")
+                        .takes_value(false),
+                )
+                .arg(
+                    clap::arg!(--strict "Fail on any problem with the response, rather than warning")
+                        .id("STRICT")
+                        .takes_value(false),
+                )
+                .arg(
+                    clap::arg!(--date <DATE> "Scheduled departure date, defaults to today, and must match a RID's date")
+                        .id("DATE")
+                        .required(false)
+                        .value_parser(clap::value_parser!(NaiveDate)),
+                ),
+        )
+        .subcommand(board_command(
+            "arrivals",
+            "Gets the arrival board of a station",
+        ))
+        .subcommand(board_command(
+            "departures",
+            "Gets the departure board of a station",
+        ))
+        .subcommand(board_command(
+            "board",
+            "Gets the arrival and departure board of a station",
+        ))
+        .subcommand(
+            Command::new("next")
+                .about("Gets the next departure from a station to each of a list of stations")
+                .arg(clap::arg!(<CRS>).required(true).value_parser(crs))
+                .arg(clap::arg!(<DESTINATIONS>...).required(true).value_parser(crs))
+                .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
+                .arg(clap::arg!(--json).id("JSON").takes_value(false))
+                .arg(
+                    clap::arg!(--fastest "Get the departure which arrives first")
+                        .id("FASTEST")
+                        .takes_value(false),
+                )
+                .arg(
+                    clap::arg!(--details "Include calling points")
+                        .id("DETAILS")
+                        .takes_value(false),
+                )
+                .arg(
+                    clap::arg!(--strict "Fail on any problem with the response, rather than warning")
+                        .id("STRICT")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            Command::new("disruptions")
+                .about("Gets the NRCC messages for one or more stations")
+                .arg(clap::arg!(<CRS>...).required(true).value_parser(crs))
+                .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
+                .arg(clap::arg!(--json).id("JSON").takes_value(false))
+                .arg(
+                    clap::arg!(--strict "Fail on any problem with the response, rather than warning")
+                        .id("STRICT")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            Command::new("sources")
+                .about("Lists the source instances which provide times, such as CIS and TD areas")
                 .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
                 .arg(clap::arg!(--json).id("JSON").takes_value(false)),
         )
+        .subcommand(
+            Command::new("reference")
+                .about("Downloads the operator and station lists, updating a reference data file")
+                .arg(clap::arg!(<FILE>).required(true))
+                .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true)),
+        )
+        .subcommand(
+            Command::new("record")
+                .about("Saves the raw response to a request, for use as a test fixture")
+                .after_help(RECORD_OPERATIONS)
+                .arg(clap::arg!(<OPERATION>).required(true))
+                .arg(clap::arg!([PARAMETERS]...))
+                .arg(clap::arg!(-t <TOKEN>).id("TOKEN").required(true))
+                .arg(
+                    clap::arg!(-o <FILE> "File to save the response to, defaults to standard output")
+                        .id("OUTPUT")
+                        .required(false),
+                )
+                .arg(
+                    clap::arg!(--tiploc "Treat locations as TIPLOCs rather than CRS codes")
+                        .id("TIPLOC")
+                        .takes_value(false),
+                )
+                .arg(
+                    clap::arg!(--date <DATE> "Scheduled departure date for query, defaults to today")
+                        .id("DATE")
+                        .required(false)
+                        .value_parser(clap::value_parser!(NaiveDate)),
+                ),
+        )
         .get_matches();
 
-    let client = Client::new();
     let rt = Builder::new_current_thread().enable_all().build().unwrap();
 
     match matches.subcommand() {
         Some(("service", sub_matches)) => {
-            let service = sub_matches.get_one::<String>("SERVICE").expect("required");
-            let token = sub_matches.get_one::<String>("TOKEN").expect("required");
+            let service = sub_matches
+                .get_one::<String>("SERVICE")
+                .expect("required")
+                .to_ascii_uppercase();
+            let json = sub_matches.is_present("JSON");
+            let date = sub_matches.get_one::<NaiveDate>("DATE").copied();
+
+            // RIDs are the date followed by a serial, anything else has to be looked up.
+            let rid = service.parse::<Rid>().ok();
+
+            if let (Some(rid), Some(date)) = (&rid, date) {
+                // RIDs given on the command line are checked, so they always have a date.
+                let rid_date = rid.date().expect("RID was parsed");
+
+                if rid_date != date {
+                    return Err(anyhow!("{} is a RID for {}, not {}", rid, rid_date, date));
+                }
+            }
+
+            if rid.is_none()
+                && service.parse::<Headcode>().is_err()
+                && service.parse::<Uid>().is_err()
+            {
+                return Err(anyhow!("{} isn't a RID, headcode or UID", service));
+            }
+
+            let reference = reference_data(sub_matches)?;
+            let options = PrettyOptions {
+                working: sub_matches.is_present("WORKING"),
+                reference: reference.as_ref(),
+            };
+
+            let client = client(sub_matches).with_lenient(!sub_matches.is_present("STRICT"));
+
+            rt.block_on(async {
+                let rid = if let Some(rid) = rid {
+                    rid
+                } else {
+                    let sdd = date.unwrap_or_else(|| uk_time(&Utc::now()).naive_local().date());
+                    let list = client
+                        .query_services(&service, sdd, &QueryOptions::default())
+                        .await
+                        .map_err(explain)?;
+
+                    for warning in &list.warnings {
+                        eprintln!("warning: {}", warning);
+                    }
+
+                    match list.services.as_slice() {
+                        [] => return Err(anyhow!("no service {} found on {}", service, sdd)),
+                        [summary] => summary.rid.clone().into_owned(),
+                        services => {
+                            eprintln!("{} services match {} on {}:", services.len(), service, sdd);
+
+                            for summary in services {
+                                eprint!("{}", summary.pretty());
+                            }
+
+                            return Err(anyhow!("use one of the RIDs above"));
+                        }
+                    }
+                };
+
+                let mut details = client.get_service_details(&rid).await.map_err(explain)?;
+
+                for warning in &details.warnings {
+                    eprintln!("warning: {}", warning);
+                }
+
+                // Reasons are only given as codes, so look up their text.
+                if details.cancel_reason.is_some() || details.delay_reason.is_some() {
+                    let list = client.get_reason_code_list().await.map_err(explain)?;
+
+                    details.resolve_reasons(&ReasonCatalogue::from(&list));
+                }
+
+                if json {
+                    println!("{}", serde_json::to_string(&details)?)
+                } else {
+                    println!("{}", details.pretty_with(&options));
+                }
+
+                Ok(())
+            })
+        }
+        Some((board @ ("arrivals" | "departures" | "board"), sub_matches)) => {
             let json = sub_matches.is_present("JSON");
+            let tiploc = sub_matches.is_present("TIPLOC");
+            let reference = reference_data(sub_matches)?;
+            let location = check_location(
+                sub_matches.get_one::<String>("LOCATION").expect("required"),
+                tiploc,
+                reference.as_ref(),
+            )?;
+            let location = location.as_str();
+            let filter = sub_matches
+                .get_one::<String>("FILTER")
+                .map(|filter| check_location(filter, tiploc, reference.as_ref()))
+                .transpose()?;
+            let options = board_options(sub_matches, filter.as_deref());
+            let pretty_options = PrettyOptions {
+                working: sub_matches.is_present("WORKING"),
+                reference: reference.as_ref(),
+            };
+
+            let client = client(sub_matches).with_lenient(!sub_matches.is_present("STRICT"));
 
             rt.block_on(async {
-                let service_details_payload =
-                    format!(service_details!(), token = token, rid = service);
-                let res = client
-                    .post("https://lite.realtime.nationalrail.co.uk/OpenLDBSVWS/ldbsv13.asmx")
-                    .body(service_details_payload)
-                    .timeout(Duration::new(5, 0))
-                    .header("Content-Type", "text/xml")
-                    .header("Accept", "text/xml")
-                    .send()
-                    .await
-                    .unwrap();
+                let board = match (board, tiploc) {
+                    ("arrivals", false) => {
+                        client.get_arrival_board_by_crs(location, &options).await
+                    }
+                    ("arrivals", true) => {
+                        client.get_arrival_board_by_tiploc(location, &options).await
+                    }
+                    ("departures", false) => {
+                        client.get_departure_board_by_crs(location, &options).await
+                    }
+                    ("departures", true) => {
+                        client
+                            .get_departure_board_by_tiploc(location, &options)
+                            .await
+                    }
+                    (_, false) => {
+                        client
+                            .get_arrival_departure_board_by_crs(location, &options)
+                            .await
+                    }
+                    (_, true) => {
+                        client
+                            .get_arrival_departure_board_by_tiploc(location, &options)
+                            .await
+                    }
+                }
+                .map_err(explain)?;
+
+                for warning in &board.warnings {
+                    eprintln!("warning: {}", warning);
+                }
+
+                if json {
+                    println!("{}", serde_json::to_string(&board)?)
+                } else {
+                    println!("{}", board.pretty_with(&pretty_options));
+                }
+
+                Ok(())
+            })
+        }
+        Some(("next", sub_matches)) => {
+            let crs = sub_matches
+                .get_one::<Crs>("CRS")
+                .expect("required")
+                .as_str();
+            let destinations: Vec<&str> = sub_matches
+                .get_many::<Crs>("DESTINATIONS")
+                .expect("required")
+                .map(|destination| destination.as_str())
+                .collect();
+            let json = sub_matches.is_present("JSON");
+            let fastest = sub_matches.is_present("FASTEST");
+            let details = sub_matches.is_present("DETAILS");
+            let options = BoardOptions::default();
+            let reference = reference_data(sub_matches)?;
+
+            for crs in destinations.iter().chain([&crs]) {
+                check_station(crs, reference.as_ref())?;
+            }
+
+            let pretty_options = PrettyOptions {
+                reference: reference.as_ref(),
+                ..PrettyOptions::default()
+            };
+
+            let client = client(sub_matches).with_lenient(!sub_matches.is_present("STRICT"));
+
+            rt.block_on(async {
+                let board = match (fastest, details) {
+                    (false, false) => {
+                        client
+                            .get_next_departures_by_crs(crs, &destinations, &options)
+                            .await
+                    }
+                    (true, false) => {
+                        client
+                            .get_fastest_departures_by_crs(crs, &destinations, &options)
+                            .await
+                    }
+                    (false, true) => {
+                        client
+                            .get_next_departures_with_details_by_crs(crs, &destinations, &options)
+                            .await
+                    }
+                    (true, true) => {
+                        client
+                            .get_fastest_departures_with_details_by_crs(
+                                crs,
+                                &destinations,
+                                &options,
+                            )
+                            .await
+                    }
+                }
+                .map_err(explain)?;
 
-                let status = res.status();
+                for warning in &board.warnings {
+                    eprintln!("warning: {}", warning);
+                }
+
+                if json {
+                    println!("{}", serde_json::to_string(&board)?)
+                } else {
+                    println!("{}", board.pretty_with(&pretty_options));
+                }
+
+                Ok(())
+            })
+        }
+        Some(("disruptions", sub_matches)) => {
+            let crs: Vec<&str> = sub_matches
+                .get_many::<Crs>("CRS")
+                .expect("required")
+                .map(|crs| crs.as_str())
+                .collect();
+            let json = sub_matches.is_present("JSON");
+            let reference = reference_data(sub_matches)?;
+
+            for crs in &crs {
+                check_station(crs, reference.as_ref())?;
+            }
+
+            let pretty_options = PrettyOptions {
+                reference: reference.as_ref(),
+                ..PrettyOptions::default()
+            };
 
-                let string = res.text().await.unwrap();
+            let client = client(sub_matches).with_lenient(!sub_matches.is_present("STRICT"));
 
-                if !status.is_success() {
-                    panic!();
+            rt.block_on(async {
+                let disruptions = client.get_disruption_list(&crs).await.map_err(explain)?;
+
+                for warning in &disruptions.warnings {
+                    eprintln!("warning: {}", warning);
                 }
 
                 if json {
-                    println!(
-                        "{}",
-                        serde_json::to_string(&ServiceDetails::try_from(&*string).unwrap())
-                            .unwrap()
-                    )
+                    println!("{}", serde_json::to_string(&disruptions)?)
                 } else {
-                    println!("{}", ServiceDetails::try_from(&*string).unwrap().pretty());
+                    println!("{}", disruptions.pretty_with(&pretty_options));
+                }
+
+                Ok(())
+            })
+        }
+        Some(("sources", sub_matches)) => {
+            let json = sub_matches.is_present("JSON");
+
+            let client = client(sub_matches);
+
+            rt.block_on(async {
+                let sources = client.get_source_instance_names().await.map_err(explain)?;
+
+                if json {
+                    println!("{}", serde_json::to_string(&sources)?)
+                } else {
+                    println!("{}", sources.pretty());
+                }
+
+                Ok(())
+            })
+        }
+        Some(("reference", sub_matches)) => {
+            let file = sub_matches.get_one::<String>("FILE").expect("required");
+
+            // Start from the existing file, so unchanged lists aren't downloaded again. Any error other than there
+            // being no file yet has to stop here, or the file would be overwritten with only what is downloaded.
+            let mut reference: ReferenceData = match fs::read_to_string(file) {
+                Ok(contents) => serde_json::from_str(&contents)
+                    .map_err(|e| anyhow!("couldn't parse reference data in {}: {}", file, e))?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => ReferenceData::new(),
+                Err(e) => return Err(anyhow!("couldn't read reference data from {}: {}", file, e)),
+            };
+
+            let client = client(sub_matches);
+
+            rt.block_on(async {
+                let tocs = client
+                    .get_toc_list(reference.toc_version())
+                    .await
+                    .map_err(explain)?;
+                reference.update_tocs(&tocs);
+
+                let stations = client
+                    .get_station_list(reference.station_version())
+                    .await
+                    .map_err(explain)?;
+                reference.update_stations(&stations);
+
+                fs::write(file, serde_json::to_string(&reference)?)?;
+
+                Ok(())
+            })
+        }
+        Some(("record", sub_matches)) => {
+            let operation = sub_matches
+                .get_one::<String>("OPERATION")
+                .expect("required");
+            let parameters: Vec<&str> = sub_matches
+                .get_many::<String>("PARAMETERS")
+                .into_iter()
+                .flatten()
+                .map(|parameter| parameter.as_str())
+                .collect();
+            let date = sub_matches
+                .get_one::<NaiveDate>("DATE")
+                .copied()
+                .unwrap_or_else(|| uk_time(&Utc::now()).naive_local().date());
+            let tiploc = sub_matches.is_present("TIPLOC");
+
+            let client = client(sub_matches);
+
+            rt.block_on(async {
+                let body = record(&client, operation, &parameters, date, tiploc).await?;
+
+                match sub_matches.get_one::<String>("OUTPUT") {
+                    Some(file) => fs::write(file, body)?,
+                    None => println!("{}", body),
                 }
-            });
 
-            Ok(())
+                Ok(())
+            })
         }
         _ => unreachable!(),
     }